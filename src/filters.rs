@@ -0,0 +1,6 @@
+pub mod box_filter;
+pub mod filter;
+pub mod gaussian_filter;
+pub mod lanczos_filter;
+pub mod mitchell_filter;
+pub mod tent_filter;
@@ -0,0 +1,27 @@
+use crate::filters::filter::Filter;
+
+pub struct BoxFilter {
+    radius: f32,
+}
+
+impl Default for BoxFilter {
+    fn default() -> Self {
+        Self::new(0.5)
+    }
+}
+
+impl BoxFilter {
+    pub fn new(radius: f32) -> Self {
+        Self { radius }
+    }
+}
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, _x: f32, _y: f32) -> f32 {
+        1.0
+    }
+}
@@ -0,0 +1,8 @@
+// Pixel reconstruction filter. A sample contributes to every pixel whose
+// center lies within `radius` of it along both axes, weighted by `evaluate`
+// at the offset from the pixel center to the sample.
+pub trait Filter {
+    fn radius(&self) -> f32;
+
+    fn evaluate(&self, x: f32, y: f32) -> f32;
+}
@@ -0,0 +1,34 @@
+use crate::filters::filter::Filter;
+
+pub struct GaussianFilter {
+    radius: f32,
+    sigma: f32,
+}
+
+impl Default for GaussianFilter {
+    fn default() -> Self {
+        Self::new(1.5, 0.5)
+    }
+}
+
+impl GaussianFilter {
+    pub fn new(radius: f32, sigma: f32) -> Self {
+        Self { radius, sigma }
+    }
+
+    // Shifted down so that the filter falls off to zero at its radius.
+    fn gaussian(&self, x: f32) -> f32 {
+        let g = |x: f32| (-x * x / (2.0 * self.sigma * self.sigma)).exp();
+        (g(x) - g(self.radius)).max(0.0)
+    }
+}
+
+impl Filter for GaussianFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        self.gaussian(x) * self.gaussian(y)
+    }
+}
@@ -0,0 +1,45 @@
+use crate::filters::filter::Filter;
+
+use std::f32::consts::PI;
+
+// Sinc windowed by a wider sinc with `tau` lobes.
+pub struct LanczosFilter {
+    radius: f32,
+    tau: f32,
+}
+
+impl Default for LanczosFilter {
+    fn default() -> Self {
+        Self::new(2.0, 2.0)
+    }
+}
+
+impl LanczosFilter {
+    pub fn new(radius: f32, tau: f32) -> Self {
+        Self { radius, tau }
+    }
+
+    fn windowed_sinc(&self, x: f32) -> f32 {
+        if x.abs() > self.radius {
+            return 0.0;
+        }
+        sinc(x) * sinc(x / self.tau)
+    }
+}
+
+impl Filter for LanczosFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        self.windowed_sinc(x) * self.windowed_sinc(y)
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-5 {
+        return 1.0;
+    }
+    (PI * x).sin() / (PI * x)
+}
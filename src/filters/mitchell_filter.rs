@@ -0,0 +1,48 @@
+use crate::filters::filter::Filter;
+
+// Mitchell-Netravali cubic, defined on [-2, 2] and stretched to the radius.
+pub struct MitchellFilter {
+    radius: f32,
+    b: f32,
+    c: f32,
+}
+
+impl Default for MitchellFilter {
+    fn default() -> Self {
+        Self::new(2.0, 1.0 / 3.0, 1.0 / 3.0)
+    }
+}
+
+impl MitchellFilter {
+    pub fn new(radius: f32, b: f32, c: f32) -> Self {
+        Self { radius, b, c }
+    }
+
+    fn mitchell(&self, x: f32) -> f32 {
+        let (b, c) = (self.b, self.c);
+        let x = (2.0 * x / self.radius).abs();
+        let value = if x > 2.0 {
+            0.0
+        } else if x > 1.0 {
+            (-b - 6.0 * c) * x * x * x
+                + (6.0 * b + 30.0 * c) * x * x
+                + (-12.0 * b - 48.0 * c) * x
+                + (8.0 * b + 24.0 * c)
+        } else {
+            (12.0 - 9.0 * b - 6.0 * c) * x * x * x
+                + (-18.0 + 12.0 * b + 6.0 * c) * x * x
+                + (6.0 - 2.0 * b)
+        };
+        value / 6.0
+    }
+}
+
+impl Filter for MitchellFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        self.mitchell(x) * self.mitchell(y)
+    }
+}
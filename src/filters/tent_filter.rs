@@ -0,0 +1,27 @@
+use crate::filters::filter::Filter;
+
+pub struct TentFilter {
+    radius: f32,
+}
+
+impl Default for TentFilter {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl TentFilter {
+    pub fn new(radius: f32) -> Self {
+        Self { radius }
+    }
+}
+
+impl Filter for TentFilter {
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        (self.radius - x.abs()).max(0.0) * (self.radius - y.abs()).max(0.0)
+    }
+}
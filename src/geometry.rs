@@ -1,5 +1,8 @@
+pub mod bounding_sphere;
 pub mod hit_record;
 pub mod hittable;
 pub mod hittable_list;
+pub mod quad;
 pub mod ray;
 pub mod sphere;
+pub mod triangle;
@@ -0,0 +1,41 @@
+use crate::math::vec3::Vec3;
+
+#[derive(Debug, Clone)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    // Centered on the centroid of the points, which is not the tightest fit
+    // but close enough for flat primitives.
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let center = points
+            .iter()
+            .fold(Vec3::default(), |acc, point| acc + point)
+            / points.len() as f32;
+        let radius = points
+            .iter()
+            .map(|point| (point - &center).norm())
+            .fold(0.0, f32::max);
+        Self { center, radius }
+    }
+
+    pub fn union(&self, other: &BoundingSphere) -> BoundingSphere {
+        let offset = &other.center - &self.center;
+        let distance = offset.norm();
+        if distance + other.radius <= self.radius {
+            return self.clone();
+        }
+        if distance + self.radius <= other.radius {
+            return other.clone();
+        }
+        let radius = 0.5 * (distance + self.radius + other.radius);
+        let center = &self.center + (radius - self.radius) / distance * offset;
+        Self { center, radius }
+    }
+}
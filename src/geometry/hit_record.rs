@@ -9,9 +9,17 @@ pub enum FacingDirection {
 pub struct HitRecord {
     pub point: Vec3,
     pub normal: Vec3,
+    pub shading_normal: Vec3,
+    pub dpdu: Vec3,
+    pub dpdv: Vec3,
     pub t: f32,
+    pub u: f32,
+    pub v: f32,
     pub facing: FacingDirection,
     pub material: Material,
+    // Position of the hit object in the scene's object list, counting from 1
+    // so that 0 stands for the background.
+    pub object_id: u32,
 }
 
 impl HitRecord {
@@ -19,19 +27,30 @@ impl HitRecord {
         point: Vec3,
         normal: Vec3,
         t: f32,
+        (u, v): (f32, f32),
+        (dpdu, dpdv): (Vec3, Vec3),
         facing: FacingDirection,
         material: Material,
     ) -> Self {
-        let normal = match facing {
-            FacingDirection::Front => normal,
-            FacingDirection::Back => -normal,
-        };
-        Self {
+        let mut record = Self {
             point,
+            shading_normal: normal.clone(),
             normal,
+            dpdu,
+            dpdv,
             t,
+            u,
+            v,
             facing,
             material,
+            object_id: 0,
+        };
+
+        record.shading_normal = record.material.shading_normal(&record);
+        if let FacingDirection::Back = record.facing {
+            record.normal = -&record.normal;
+            record.shading_normal = -&record.shading_normal;
         }
+        record
     }
 }
@@ -1,6 +1,13 @@
-use crate::geometry::{hit_record::HitRecord, ray::Ray};
+use crate::geometry::{bounding_sphere::BoundingSphere, hit_record::HitRecord, ray::Ray};
 use crate::math::interval::Interval;
 
-pub trait Hittable {
+// Scenes are shared by the threads tracing rays into them.
+pub trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, hit_interval: &Interval<f32>) -> Option<HitRecord>;
+
+    // Encloses the surfaces with specular materials, the only ones that can
+    // start a caustic path.
+    fn specular_bounds(&self) -> Option<BoundingSphere> {
+        None
+    }
 }
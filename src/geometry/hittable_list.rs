@@ -1,10 +1,18 @@
-use crate::geometry::{hit_record::HitRecord, hittable::Hittable, ray::Ray};
+use crate::geometry::{
+    bounding_sphere::BoundingSphere, hit_record::HitRecord, hittable::Hittable, ray::Ray,
+};
 use crate::math::interval::Interval;
 
 pub struct HittableList {
     objects: Vec<Box<dyn Hittable>>,
 }
 
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HittableList {
     pub fn new() -> Self {
         Self { objects: vec![] }
@@ -20,13 +28,34 @@ impl HittableList {
     pub fn clear(&mut self) {
         self.objects.clear();
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
 }
 
 impl Hittable for HittableList {
     fn hit(&self, ray: &Ray, hit_interval: &Interval<f32>) -> Option<HitRecord> {
         self.objects
             .iter()
-            .filter_map(|x| x.hit(&ray, &hit_interval))
+            .enumerate()
+            .filter_map(|(index, x)| {
+                x.hit(ray, hit_interval).map(|mut record| {
+                    record.object_id = index as u32 + 1;
+                    record
+                })
+            })
             .min_by(|x, y| x.t.total_cmp(&y.t))
     }
+
+    fn specular_bounds(&self) -> Option<BoundingSphere> {
+        self.objects
+            .iter()
+            .filter_map(|x| x.specular_bounds())
+            .reduce(|acc, bounds| acc.union(&bounds))
+    }
 }
@@ -0,0 +1,91 @@
+use crate::geometry::{
+    bounding_sphere::BoundingSphere,
+    hit_record::{FacingDirection, HitRecord},
+    hittable::Hittable,
+    ray::Ray,
+};
+use crate::materials::material::Material;
+use crate::math::{interval::Interval, vec3::Vec3};
+
+pub struct Quad {
+    pub corner: Vec3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Material,
+    normal: Vec3,
+    offset: f32,
+    w: Vec3,
+}
+
+impl Quad {
+    pub fn new(corner: Vec3, u: Vec3, v: Vec3, material: Material) -> Self {
+        let n = u.cross(&v);
+        let normal = n.unit_vector();
+        let offset = normal.dot(&corner);
+        let w = &n / n.norm_squared();
+        Self {
+            corner,
+            u,
+            v,
+            material,
+            normal,
+            offset,
+            w,
+        }
+    }
+
+    fn planar_coordinates(&self, point: &Vec3) -> Option<(f32, f32)> {
+        let planar = point - &self.corner;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        let unit = Interval::new(0.0, 1.0);
+        match unit.contains(alpha) && unit.contains(beta) {
+            true => Some((alpha, beta)),
+            false => None,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, hit_interval: &Interval<f32>) -> Option<HitRecord> {
+        let denom = self.normal.dot(ray.direction());
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.offset - self.normal.dot(ray.origin())) / denom;
+        if !hit_interval.surrounds(t) {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let uv = self.planar_coordinates(&point)?;
+        if !self.material.is_opaque_at(uv, &point, ray) {
+            return None;
+        }
+        let facing = match denom < 0.0 {
+            true => FacingDirection::Front,
+            false => FacingDirection::Back,
+        };
+        Some(HitRecord::new(
+            point,
+            self.normal.clone(),
+            t,
+            uv,
+            (self.u.clone(), self.v.clone()),
+            facing,
+            self.material.clone(),
+        ))
+    }
+
+    fn specular_bounds(&self) -> Option<BoundingSphere> {
+        self.material.is_specular().then(|| {
+            BoundingSphere::from_points(&[
+                self.corner.clone(),
+                &self.corner + &self.u,
+                &self.corner + &self.v,
+                &self.corner + &self.u + &self.v,
+            ])
+        })
+    }
+}
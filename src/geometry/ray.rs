@@ -1,5 +1,6 @@
 use crate::math::vec3::Vec3;
 
+#[derive(Clone)]
 pub struct Ray {
     origin: Vec3,
     direction: Vec3,
@@ -19,6 +20,6 @@ impl Ray {
     }
 
     pub fn at(&self, t: f32) -> Vec3 {
-        return &self.origin + t * &self.direction;
+        &self.origin + t * &self.direction
     }
 }
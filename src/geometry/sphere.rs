@@ -1,4 +1,5 @@
 use crate::geometry::{
+    bounding_sphere::BoundingSphere,
     hit_record::{FacingDirection, HitRecord},
     hittable::Hittable,
     ray::Ray,
@@ -50,6 +51,25 @@ impl Sphere {
         }
     }
 
+    fn uv(outward_normal: &Vec3) -> (f32, f32) {
+        let theta = (-outward_normal.y).acos();
+        let phi = (-outward_normal.z).atan2(outward_normal.x) + std::f32::consts::PI;
+        (
+            0.5 * phi / std::f32::consts::PI,
+            theta / std::f32::consts::PI,
+        )
+    }
+
+    fn tangents(&self, outward_normal: &Vec3) -> (Vec3, Vec3) {
+        let (x, y, z) = (outward_normal.x, outward_normal.y, outward_normal.z);
+        let sin_theta = (x * x + z * z).sqrt().max(1e-6);
+        let dpdu = 2.0 * std::f32::consts::PI * self.radius * Vec3::new(z, 0.0, -x);
+        let dpdv = std::f32::consts::PI
+            * self.radius
+            * Vec3::new(-x * y / sin_theta, sin_theta, -z * y / sin_theta);
+        (dpdu, dpdv)
+    }
+
     fn root(&self, ray: &Ray, hit_interval: &Interval<f32>) -> Option<f32> {
         let oc = ray.origin() - &self.center;
         let a = ray.direction().norm_squared();
@@ -69,11 +89,11 @@ impl Sphere {
         hit_interval: &Interval<f32>,
     ) -> Option<f32> {
         let sqrtd = discriminant.eval().sqrt();
-        let root = -1.0 * (discriminant.b_halfs + sqrtd) / discriminant.a;
+        let root = -(discriminant.b_halfs + sqrtd) / discriminant.a;
         match hit_interval.surrounds(root) {
             true => Some(root),
             false => {
-                let root = -1.0 * (discriminant.b_halfs - sqrtd) / discriminant.a;
+                let root = -(discriminant.b_halfs - sqrtd) / discriminant.a;
                 match hit_interval.surrounds(root) {
                     true => Some(root),
                     false => None,
@@ -85,7 +105,7 @@ impl Sphere {
 
 impl Hittable for Sphere {
     fn hit(&self, ray: &Ray, hit_interval: &Interval<f32>) -> Option<HitRecord> {
-        match self.root(&ray, hit_interval) {
+        match self.root(ray, hit_interval) {
             Some(root) => {
                 let t = root;
                 let point = ray.at(t);
@@ -94,10 +114,17 @@ impl Hittable for Sphere {
                     true => FacingDirection::Front,
                     false => FacingDirection::Back,
                 };
+                let uv = Sphere::uv(&normal);
+                if !self.material.is_opaque_at(uv, &point, ray) {
+                    return self.hit(ray, &Interval::new(t, hit_interval.ub));
+                }
+                let tangents = self.tangents(&normal);
                 Some(HitRecord::new(
                     point,
                     normal,
                     t,
+                    uv,
+                    tangents,
                     facing,
                     self.material.clone(),
                 ))
@@ -105,4 +132,10 @@ impl Hittable for Sphere {
             None => None,
         }
     }
+
+    fn specular_bounds(&self) -> Option<BoundingSphere> {
+        self.material
+            .is_specular()
+            .then(|| BoundingSphere::new(self.center.clone(), self.radius))
+    }
 }
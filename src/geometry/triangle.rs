@@ -0,0 +1,83 @@
+use crate::geometry::{
+    bounding_sphere::BoundingSphere,
+    hit_record::{FacingDirection, HitRecord},
+    hittable::Hittable,
+    ray::Ray,
+};
+use crate::materials::material::Material;
+use crate::math::{interval::Interval, vec3::Vec3};
+
+pub struct Triangle {
+    pub vertices: [Vec3; 3],
+    pub material: Material,
+    edges: (Vec3, Vec3),
+    normal: Vec3,
+}
+
+impl Triangle {
+    pub fn new(a: Vec3, b: Vec3, c: Vec3, material: Material) -> Self {
+        let edges = (&b - &a, &c - &a);
+        let normal = edges.0.cross(&edges.1).unit_vector();
+        Self {
+            vertices: [a, b, c],
+            material,
+            edges,
+            normal,
+        }
+    }
+
+    // Möller–Trumbore intersection, returning the distance and barycentric coordinates.
+    fn intersect(&self, ray: &Ray) -> Option<(f32, f32, f32)> {
+        let (e1, e2) = &self.edges;
+        let p = ray.direction().cross(e2);
+        let det = e1.dot(&p);
+        if det.abs() < 1e-8 {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let s = ray.origin() - &self.vertices[0];
+        let b1 = inv_det * s.dot(&p);
+        if !(0.0..=1.0).contains(&b1) {
+            return None;
+        }
+
+        let q = s.cross(e1);
+        let b2 = inv_det * ray.direction().dot(&q);
+        if b2 < 0.0 || b1 + b2 > 1.0 {
+            return None;
+        }
+
+        Some((inv_det * e2.dot(&q), b1, b2))
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, hit_interval: &Interval<f32>) -> Option<HitRecord> {
+        let (t, b1, b2) = self.intersect(ray)?;
+        let point = ray.at(t);
+        if !hit_interval.surrounds(t) || !self.material.is_opaque_at((b1, b2), &point, ray) {
+            return None;
+        }
+
+        let facing = match ray.direction().dot(&self.normal) < 0.0 {
+            true => FacingDirection::Front,
+            false => FacingDirection::Back,
+        };
+        Some(HitRecord::new(
+            point,
+            self.normal.clone(),
+            t,
+            (b1, b2),
+            self.edges.clone(),
+            facing,
+            self.material.clone(),
+        ))
+    }
+
+    fn specular_bounds(&self) -> Option<BoundingSphere> {
+        self.material
+            .is_specular()
+            .then(|| BoundingSphere::from_points(&self.vertices))
+    }
+}
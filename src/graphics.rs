@@ -1,4 +1,17 @@
+pub mod adaptive_sampling;
+pub mod aov;
+pub mod camera;
+pub mod cancellation;
+pub mod checkpoint;
+pub mod color_space;
+pub mod denoiser;
+pub mod display_transform;
+pub mod film;
 pub mod image;
 pub mod pixel;
-pub mod camera;
+pub mod progress;
+pub mod progressive;
 pub mod renderer;
+pub mod scene;
+pub mod statistics;
+pub mod tone_mapping;
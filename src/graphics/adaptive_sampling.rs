@@ -0,0 +1,36 @@
+use crate::graphics::film::Film;
+
+// Stops sampling a pixel once the relative standard error of its luminance
+// drops below `relative_error`, after at least `min_samples` and at most
+// `max_samples` samples.
+pub struct AdaptiveSampling {
+    pub min_samples: u32,
+    pub max_samples: u32,
+    pub relative_error: f32,
+    pub sample_count_map: Option<String>,
+}
+
+impl AdaptiveSampling {
+    pub fn new(min_samples: u32, max_samples: u32, relative_error: f32) -> Self {
+        Self {
+            min_samples: min_samples.max(2),
+            max_samples: max_samples.max(min_samples.max(2)),
+            relative_error,
+            sample_count_map: None,
+        }
+    }
+
+    // Writes a greyscale image of the samples spent per pixel, normalized to
+    // the maximum budget, next to the render. The map is always a linear PFM,
+    // whatever the extension of `path`.
+    pub fn with_sample_count_map(mut self, path: &str) -> Self {
+        self.sample_count_map = Some(path.to_string());
+        self
+    }
+
+    pub fn is_converged(&self, film: &Film, i: u32, j: u32) -> bool {
+        let samples = film.sample_count(i, j);
+        samples >= self.max_samples
+            || (samples >= self.min_samples && film.relative_error(i, j) <= self.relative_error)
+    }
+}
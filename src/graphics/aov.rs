@@ -0,0 +1,148 @@
+use crate::geometry::{hit_record::HitRecord, ray::Ray};
+use crate::graphics::{
+    checkpoint::{self, Accumulator},
+    pixel::Pixel,
+};
+use crate::math::vec3::Vec3;
+
+use std::io::{Read, Result, Write};
+
+// Auxiliary buffers describing the first surface seen through each pixel.
+// Pixels that see the background hold zero in every buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aov {
+    // Distance from the camera along the ray.
+    Depth,
+    // World space shading normal.
+    Normal,
+    Albedo,
+    MaterialId,
+    ObjectId,
+    // World space position.
+    Position,
+}
+
+impl Aov {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::MaterialId => "material_id",
+            Aov::ObjectId => "object_id",
+            Aov::Position => "position",
+        }
+    }
+
+    // IDs cannot be averaged, so they keep the first sample of every pixel.
+    pub fn is_id(&self) -> bool {
+        matches!(self, Aov::MaterialId | Aov::ObjectId)
+    }
+
+    pub fn is_color(&self) -> bool {
+        matches!(self, Aov::Albedo)
+    }
+
+    fn value(&self, ray: &Ray, record: &HitRecord) -> Pixel {
+        let splat = |v: f32| Pixel::from(&Vec3::new(v, v, v));
+        match self {
+            Aov::Depth => splat(record.t * ray.direction().norm()),
+            Aov::Normal => Pixel::from(&record.shading_normal),
+            Aov::Albedo => record.material.albedo(),
+            Aov::MaterialId => splat(record.material.id() as f32),
+            Aov::ObjectId => splat(record.object_id as f32),
+            Aov::Position => Pixel::from(&record.point),
+        }
+    }
+}
+
+struct AovLayer {
+    aov: Aov,
+    values: Vec<Pixel>,
+    counts: Vec<u32>,
+}
+
+pub struct AovFilm {
+    width: u32,
+    layers: Vec<AovLayer>,
+}
+
+impl AovFilm {
+    pub fn new(width: u32, height: u32, aovs: &[Aov]) -> Self {
+        let size = (width * height) as usize;
+        let layers = aovs
+            .iter()
+            .map(|&aov| AovLayer {
+                aov,
+                values: vec![Pixel::default(); size],
+                counts: vec![0; size],
+            })
+            .collect();
+        Self { width, layers }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    pub fn aovs(&self) -> impl Iterator<Item = Aov> + '_ {
+        self.layers.iter().map(|layer| layer.aov)
+    }
+
+    pub fn add_sample(&mut self, i: u32, j: u32, ray: &Ray, record: Option<&HitRecord>) {
+        let index = (j * self.width + i) as usize;
+        for layer in self.layers.iter_mut() {
+            let count = layer.counts[index];
+            if layer.aov.is_id() && count > 0 {
+                continue;
+            }
+            if let Some(record) = record {
+                layer.values[index] += layer.aov.value(ray, record);
+            }
+            layer.counts[index] = count + 1;
+        }
+    }
+
+    pub fn pixel(&self, aov: Aov, i: u32, j: u32) -> Pixel {
+        let index = (j * self.width + i) as usize;
+        self.layers
+            .iter()
+            .find(|layer| layer.aov == aov)
+            .map(|layer| (1.0 / layer.counts[index].max(1) as f32) * layer.values[index].clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Accumulator for AovFilm {
+    fn save(&self, w: &mut dyn Write) -> Result<()> {
+        checkpoint::write_u32(w, self.layers.len() as u32)?;
+        for layer in self.layers.iter() {
+            checkpoint::write_u32(w, layer.aov as u32)?;
+            for (value, &count) in layer.values.iter().zip(layer.counts.iter()) {
+                checkpoint::write_pixel(w, value)?;
+                checkpoint::write_u32(w, count)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn restore(&mut self, r: &mut dyn Read) -> Result<()> {
+        if checkpoint::read_u32(r)? as usize != self.layers.len() {
+            return Err(checkpoint::invalid(
+                "checkpoint AOVs differ from the render",
+            ));
+        }
+        for layer in self.layers.iter_mut() {
+            if checkpoint::read_u32(r)? != layer.aov as u32 {
+                return Err(checkpoint::invalid(
+                    "checkpoint AOVs differ from the render",
+                ));
+            }
+            for index in 0..layer.values.len() {
+                layer.values[index] = checkpoint::read_pixel(r)?;
+                layer.counts[index] = checkpoint::read_u32(r)?;
+            }
+        }
+        Ok(())
+    }
+}
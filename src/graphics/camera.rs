@@ -1,4 +1,6 @@
+use crate::geometry::ray::Ray;
 use crate::math::vec3::Vec3;
+use crate::sampling::sampler::Sampler;
 
 pub struct CameraParameters {
     aspect_ratio: f32,
@@ -72,13 +74,25 @@ pub struct Camera {
     pub zero: Vec3,
     pub du: Vec3,
     pub dv: Vec3,
+    pub image_width: u32,
+    pub image_height: u32,
     pub samples_per_pixel: u32,
     pub max_ray_bounces: u32,
     pub defocus_angle: f32,
+    pub focus_dist: f32,
+    pub lens_radius: f32,
     pub frame: CameraFrame,
     pub defocus_disk: DefocusDisk,
 }
 
+pub struct CameraSample {
+    pub direction: Vec3,
+    pub distance: f32,
+    pub importance: f32,
+    pub pdf: f32,
+    pub raster: (f32, f32),
+}
+
 impl Default for Camera {
     fn default() -> Self {
         const ASPECT_RATIO: f32 = 16.0 / 9.0;
@@ -138,11 +152,118 @@ impl Camera {
             zero,
             du,
             dv,
+            image_width: params.image_width,
+            image_height: params.image_height,
             samples_per_pixel,
             max_ray_bounces,
             defocus_angle,
+            focus_dist: params.focus_dist,
+            lens_radius: match defocus_angle > 0.0 {
+                true => defocus_radius,
+                false => 0.0,
+            },
             frame,
             defocus_disk,
         }
     }
+
+    // Generates a ray through a continuous raster position, with pixel (i, j)
+    // covering [i, i + 1) x [j, j + 1).
+    pub fn generate_ray(&self, (x, y): (f32, f32), sampler: &mut dyn Sampler) -> Ray {
+        let target = &self.zero + (x - 0.5) * &self.du + (y - 0.5) * &self.dv;
+        let origin = self.lens_sample(sampler);
+        let direction = target - &origin;
+        Ray::new(origin, direction)
+    }
+
+    // Continuous raster position of a ray leaving the lens at `origin`, with
+    // pixel (i, j) covering [i, i + 1) x [j, j + 1).
+    pub fn raster(&self, origin: &Vec3, direction: &Vec3) -> Option<(f32, f32)> {
+        let direction = direction.unit_vector();
+        let cos_theta = -direction.dot(&self.frame.w);
+        if cos_theta <= 0.0 {
+            return None;
+        }
+
+        let focus = origin + self.focus_dist / cos_theta * direction;
+        let offset = focus - &self.zero;
+        let x = offset.dot(&self.du) / self.du.norm_squared() + 0.5;
+        let y = offset.dot(&self.dv) / self.dv.norm_squared() + 0.5;
+
+        match (0.0..self.image_width as f32).contains(&x)
+            && (0.0..self.image_height as f32).contains(&y)
+        {
+            true => Some((x, y)),
+            false => None,
+        }
+    }
+
+    // Importance emitted along a ray leaving the lens, normalized so that it
+    // integrates to one over the image.
+    pub fn importance(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        if self.raster(origin, direction).is_none() {
+            return 0.0;
+        }
+        let cos_theta = -direction.unit_vector().dot(&self.frame.w);
+        let cos2_theta = cos_theta * cos_theta;
+        1.0 / (self.unit_film_area() * self.lens_area() * cos2_theta * cos2_theta)
+    }
+
+    // Solid angle density with which camera rays are generated along `direction`.
+    pub fn pdf_direction(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        if self.raster(origin, direction).is_none() {
+            return 0.0;
+        }
+        let cos_theta = -direction.unit_vector().dot(&self.frame.w);
+        1.0 / (self.unit_film_area() * cos_theta * cos_theta * cos_theta)
+    }
+
+    // Samples a point on the lens as seen from `point`, returning the direction
+    // from `point` towards it.
+    pub fn sample_wi(&self, point: &Vec3, sampler: &mut dyn Sampler) -> Option<CameraSample> {
+        let lens_point = self.lens_sample(sampler);
+
+        let to_lens = &lens_point - point;
+        let distance = to_lens.norm();
+        if distance <= 0.0 {
+            return None;
+        }
+        let direction = to_lens / distance;
+        let outgoing = -&direction;
+
+        let raster = self.raster(&lens_point, &outgoing)?;
+        let cos_lens = direction.dot(&self.frame.w).abs();
+        let pdf = distance * distance / (cos_lens * self.lens_area());
+        Some(CameraSample {
+            importance: self.importance(&lens_point, &outgoing),
+            direction,
+            distance,
+            pdf,
+            raster,
+        })
+    }
+
+    fn lens_sample(&self, sampler: &mut dyn Sampler) -> Vec3 {
+        match self.lens_radius > 0.0 {
+            true => {
+                let p = Vec3::random_in_unit_disk(sampler);
+                &self.frame.center + p.x * &self.defocus_disk.u + p.y * &self.defocus_disk.v
+            }
+            false => self.frame.center.clone(),
+        }
+    }
+
+    // A pinhole camera is treated as a lens of unit area.
+    fn lens_area(&self) -> f32 {
+        match self.lens_radius > 0.0 {
+            true => std::f32::consts::PI * self.lens_radius * self.lens_radius,
+            false => 1.0,
+        }
+    }
+
+    fn unit_film_area(&self) -> f32 {
+        let width = self.image_width as f32 * self.du.norm();
+        let height = self.image_height as f32 * self.dv.norm();
+        width * height / (self.focus_dist * self.focus_dist)
+    }
 }
@@ -0,0 +1,184 @@
+use crate::graphics::pixel::Pixel;
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Result, Write};
+use std::time::Duration;
+
+const MAGIC: &[u8; 4] = b"RTCK";
+const VERSION: u32 = 2;
+
+// Periodically saves the accumulated film and integrator state to `path`
+// between passes. A render finding a checkpoint at start resumes after the
+// last saved pass and, as sample streams only depend on pixel, sample index
+// and seed, ends with the same image as an uninterrupted run. The file is
+// removed once the render completes.
+pub struct Checkpoint {
+    pub path: String,
+    pub interval: Duration,
+}
+
+// Settings that decide the sample streams. A checkpoint written with others
+// cannot be resumed.
+#[derive(Debug, PartialEq)]
+pub struct RenderIdentity {
+    pub seed: u64,
+    pub sampler: String,
+    pub integrator: String,
+}
+
+impl RenderIdentity {
+    fn save(&self, w: &mut dyn Write) -> Result<()> {
+        write_u64(w, self.seed)?;
+        write_str(w, &self.sampler)?;
+        write_str(w, &self.integrator)
+    }
+
+    fn read(r: &mut dyn Read) -> Result<Self> {
+        Ok(Self {
+            seed: read_u64(r)?,
+            sampler: read_str(r)?,
+            integrator: read_str(r)?,
+        })
+    }
+}
+
+// Accumulated state that is saved in a checkpoint.
+pub trait Accumulator {
+    fn save(&self, w: &mut dyn Write) -> Result<()>;
+
+    fn restore(&mut self, r: &mut dyn Read) -> Result<()>;
+}
+
+impl Checkpoint {
+    pub fn new(path: &str, interval: Duration) -> Self {
+        Self {
+            path: path.to_string(),
+            interval,
+        }
+    }
+
+    pub fn is_due(&self, since_checkpoint: Duration) -> bool {
+        since_checkpoint >= self.interval
+    }
+
+    // Written to a temporary file first, so that a crash while saving leaves
+    // the previous checkpoint intact.
+    pub fn save(
+        &self,
+        next_pass: u32,
+        identity: &RenderIdentity,
+        state: &[&dyn Accumulator],
+    ) -> Result<()> {
+        let temporary = format!("{}.tmp", self.path);
+        {
+            let mut w = BufWriter::new(File::create(&temporary)?);
+            w.write_all(MAGIC)?;
+            write_u32(&mut w, VERSION)?;
+            write_u32(&mut w, next_pass)?;
+            identity.save(&mut w)?;
+            for accumulator in state {
+                accumulator.save(&mut w)?;
+            }
+            w.flush()?;
+        }
+        fs::rename(temporary, &self.path)
+    }
+
+    // Returns the pass to continue with, or None without a checkpoint.
+    pub fn restore(
+        &self,
+        identity: &RenderIdentity,
+        state: &mut [&mut dyn Accumulator],
+    ) -> Result<Option<u32>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut r = BufReader::new(file);
+
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(&mut r)? != VERSION {
+            return Err(invalid("not a checkpoint of this renderer version"));
+        }
+        let next_pass = read_u32(&mut r)?;
+        if RenderIdentity::read(&mut r)? != *identity {
+            return Err(invalid("checkpoint was written with different settings"));
+        }
+        for accumulator in state.iter_mut() {
+            accumulator.restore(&mut r)?;
+        }
+        Ok(Some(next_pass))
+    }
+
+    pub fn remove(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn write_u32(w: &mut dyn Write, value: u32) -> Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+pub fn write_u64(w: &mut dyn Write, value: u64) -> Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+pub fn write_f32(w: &mut dyn Write, value: f32) -> Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+pub fn write_str(w: &mut dyn Write, value: &str) -> Result<()> {
+    write_u32(w, value.len() as u32)?;
+    w.write_all(value.as_bytes())
+}
+
+pub fn write_pixel(w: &mut dyn Write, pixel: &Pixel) -> Result<()> {
+    write_f32(w, pixel.r)?;
+    write_f32(w, pixel.g)?;
+    write_f32(w, pixel.b)
+}
+
+pub fn read_u32(r: &mut dyn Read) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub fn read_u64(r: &mut dyn Read) -> Result<u64> {
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub fn read_f32(r: &mut dyn Read) -> Result<f32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+pub fn read_str(r: &mut dyn Read) -> Result<String> {
+    let len = read_u32(r)? as u64;
+    let mut bytes = vec![];
+    r.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(Error::from(ErrorKind::UnexpectedEof));
+    }
+    String::from_utf8(bytes).map_err(|_| invalid("string is not UTF-8"))
+}
+
+pub fn read_pixel(r: &mut dyn Read) -> Result<Pixel> {
+    Ok(Pixel {
+        r: read_f32(r)?,
+        g: read_f32(r)?,
+        b: read_f32(r)?,
+    })
+}
+
+pub fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,189 @@
+use crate::graphics::pixel::Pixel;
+use crate::math::vec3::Vec3;
+
+// RGB color spaces with their primaries and white points. Conversions go
+// through CIE XYZ adapted to D65 with the Bradford transform. `Srgb` holds
+// values encoded with the sRGB transfer curve, as found in 8 bit images;
+// the linear spaces are suitable for rendering.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    LinearSrgb,
+    Srgb,
+    AcesCg,
+}
+
+const LINEAR_SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_390_8, 0.357_584_3, 0.180_480_8],
+    [0.212_639, 0.715_168_7, 0.072_192_3],
+    [0.019_330_8, 0.119_194_8, 0.950_532_2],
+];
+
+const XYZ_TO_LINEAR_SRGB: [[f32; 3]; 3] = [
+    [3.240_97, -1.537_383_2, -0.498_610_8],
+    [-0.969_243_6, 1.875_967_5, 0.041_555_1],
+    [0.055_630_1, -0.203_977, 1.056_971_5],
+];
+
+// ACES AP1 primaries, Bradford-adapted from the ACES white point to D65.
+const ACESCG_TO_XYZ: [[f32; 3]; 3] = [
+    [0.652_237_5, 0.128_236_1, 0.169_982_2],
+    [0.267_672_2, 0.674_34, 0.057_987_8],
+    [-0.005_381_8, 0.001_369_1, 1.093_070_5],
+];
+
+const XYZ_TO_ACESCG: [[f32; 3]; 3] = [
+    [1.660_585_3, -0.315_295_6, -0.241_509_3],
+    [-0.659_926_1, 1.608_391_5, 0.017_298_6],
+    [0.009_002_6, -0.003_566_9, 0.913_643_3],
+];
+
+impl ColorSpace {
+    pub fn to_xyz(&self, pixel: &Pixel) -> Vec3 {
+        let (m, linear) = match self {
+            ColorSpace::LinearSrgb => (&LINEAR_SRGB_TO_XYZ, pixel.clone()),
+            ColorSpace::Srgb => (&LINEAR_SRGB_TO_XYZ, map(pixel, srgb_decode)),
+            ColorSpace::AcesCg => (&ACESCG_TO_XYZ, pixel.clone()),
+        };
+        let (x, y, z) = transform(m, (linear.r, linear.g, linear.b));
+        Vec3::new(x, y, z)
+    }
+
+    pub fn from_xyz(&self, xyz: &Vec3) -> Pixel {
+        let m = match self {
+            ColorSpace::LinearSrgb | ColorSpace::Srgb => &XYZ_TO_LINEAR_SRGB,
+            ColorSpace::AcesCg => &XYZ_TO_ACESCG,
+        };
+        let (r, g, b) = transform(m, (xyz.x, xyz.y, xyz.z));
+        let linear = Pixel { r, g, b };
+        match self {
+            ColorSpace::Srgb => map(&linear, srgb_encode),
+            _ => linear,
+        }
+    }
+
+    pub fn convert(&self, pixel: &Pixel, to: ColorSpace) -> Pixel {
+        if *self == to {
+            return pixel.clone();
+        }
+        to.from_xyz(&self.to_xyz(pixel))
+    }
+}
+
+pub fn srgb_encode(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        12.92 * value
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn srgb_decode(value: f32) -> f32 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn map<F: Fn(f32) -> f32>(pixel: &Pixel, f: F) -> Pixel {
+    Pixel {
+        r: f(pixel.r),
+        g: f(pixel.g),
+        b: f(pixel.b),
+    }
+}
+
+fn transform(m: &[[f32; 3]; 3], (x, y, z): (f32, f32, f32)) -> (f32, f32, f32) {
+    (
+        m[0][0] * x + m[0][1] * y + m[0][2] * z,
+        m[1][0] * x + m[1][1] * y + m[1][2] * z,
+        m[2][0] * x + m[2][1] * y + m[2][2] * z,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors() -> Vec<Pixel> {
+        [
+            (0.0, 0.0, 0.0),
+            (1.0, 1.0, 1.0),
+            (0.18, 0.18, 0.18),
+            (1.0, 0.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (0.0, 0.0, 1.0),
+            (0.8, 0.4, 0.1),
+            (4.0, 2.5, 0.3),
+        ]
+        .into_iter()
+        .map(|(r, g, b)| Pixel { r, g, b })
+        .collect()
+    }
+
+    fn assert_close(a: &Pixel, b: &Pixel) {
+        let close = |x: f32, y: f32| (x - y).abs() <= 1e-4 * x.abs().max(1.0);
+        assert!(
+            close(a.r, b.r) && close(a.g, b.g) && close(a.b, b.b),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn round_trips_through_xyz() {
+        for space in [ColorSpace::LinearSrgb, ColorSpace::Srgb, ColorSpace::AcesCg] {
+            for color in colors() {
+                assert_close(&space.from_xyz(&space.to_xyz(&color)), &color);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_srgb_through_acescg() {
+        for color in colors() {
+            let acescg = ColorSpace::LinearSrgb.convert(&color, ColorSpace::AcesCg);
+            assert_close(
+                &ColorSpace::AcesCg.convert(&acescg, ColorSpace::LinearSrgb),
+                &color,
+            );
+
+            let encoded = ColorSpace::LinearSrgb.convert(&color, ColorSpace::Srgb);
+            let acescg = ColorSpace::Srgb.convert(&encoded, ColorSpace::AcesCg);
+            assert_close(
+                &ColorSpace::AcesCg.convert(&acescg, ColorSpace::Srgb),
+                &encoded,
+            );
+        }
+    }
+
+    // Both spaces share the D65 white, so neutrals keep their values.
+    #[test]
+    fn keeps_neutrals_neutral() {
+        let grey = Pixel {
+            r: 0.18,
+            g: 0.18,
+            b: 0.18,
+        };
+        assert_close(
+            &ColorSpace::LinearSrgb.convert(&grey, ColorSpace::AcesCg),
+            &grey,
+        );
+        let xyz = ColorSpace::LinearSrgb.to_xyz(&grey);
+        assert!((xyz.y - 0.18).abs() < 1e-4);
+    }
+
+    #[test]
+    fn converts_the_sky_into_the_scene_space() {
+        let direction = Vec3::new(0.3, 0.5, -1.0);
+        let srgb = Pixel::from_miss(&direction, ColorSpace::LinearSrgb);
+        let acescg = Pixel::from_miss(&direction, ColorSpace::AcesCg);
+        assert_close(
+            &ColorSpace::AcesCg.convert(&acescg, ColorSpace::LinearSrgb),
+            &srgb,
+        );
+        assert!((acescg.b - srgb.b).abs() > 1e-3);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::graphics::{
+    aov::{Aov, AovFilm},
+    film::Film,
+    pixel::Pixel,
+};
+
+// Joint cross-bilateral filter guided by the albedo, normal and depth AOVs.
+// Radiance is divided by albedo before filtering and multiplied back after,
+// so that texture detail survives, and color differences are measured
+// relative to the estimated pixel variance, so that flat but noisy regions
+// are smoothed while true edges are kept.
+pub struct Denoiser {
+    radius: u32,
+    sigma_spatial: f32,
+    sigma_color: f32,
+    sigma_normal: f32,
+    sigma_albedo: f32,
+    sigma_depth: f32,
+}
+
+// AOVs the denoiser reads in addition to the film.
+pub const DENOISER_FEATURES: [Aov; 3] = [Aov::Albedo, Aov::Normal, Aov::Depth];
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            radius: 5,
+            sigma_spatial: 2.0,
+            sigma_color: 2.0,
+            sigma_normal: 0.2,
+            sigma_albedo: 0.1,
+            sigma_depth: 0.05,
+        }
+    }
+}
+
+struct Features {
+    irradiance: Pixel,
+    variance: f32,
+    albedo: Pixel,
+    normal: Pixel,
+    depth: f32,
+}
+
+impl Denoiser {
+    pub fn with_radius(mut self, radius: u32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_sigma_spatial(mut self, sigma: f32) -> Self {
+        self.sigma_spatial = sigma;
+        self
+    }
+
+    // Scales the color distance, which is already normalized by variance.
+    pub fn with_sigma_color(mut self, sigma: f32) -> Self {
+        self.sigma_color = sigma;
+        self
+    }
+
+    pub fn with_sigma_normal(mut self, sigma: f32) -> Self {
+        self.sigma_normal = sigma;
+        self
+    }
+
+    pub fn with_sigma_albedo(mut self, sigma: f32) -> Self {
+        self.sigma_albedo = sigma;
+        self
+    }
+
+    // Depth differences are measured relative to the depth of the center.
+    pub fn with_sigma_depth(mut self, sigma: f32) -> Self {
+        self.sigma_depth = sigma;
+        self
+    }
+
+    pub fn denoise(&self, film: &Film, aovs: &AovFilm) -> Vec<Pixel> {
+        let (width, height) = (film.width, film.height);
+        let features = (0..height)
+            .flat_map(|j| (0..width).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let albedo = aovs.pixel(Aov::Albedo, i, j);
+                let demodulation = Denoiser::demodulation(&albedo);
+                let luminance = demodulation.luminance();
+                let color = film.pixel(i, j);
+                Features {
+                    irradiance: Pixel {
+                        r: color.r / demodulation.r,
+                        g: color.g / demodulation.g,
+                        b: color.b / demodulation.b,
+                    },
+                    variance: film.variance(i, j) / (luminance * luminance),
+                    albedo,
+                    normal: aovs.pixel(Aov::Normal, i, j),
+                    depth: aovs.pixel(Aov::Depth, i, j).r,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let radius = self.radius as i64;
+        let index = |i: i64, j: i64| (j * width as i64 + i) as usize;
+
+        (0..height as i64)
+            .flat_map(|j| (0..width as i64).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let center = &features[index(i, j)];
+                let mut sum = Pixel::default();
+                let mut weight_sum = 0.0;
+
+                for y in (j - radius).max(0)..=(j + radius).min(height as i64 - 1) {
+                    for x in (i - radius).max(0)..=(i + radius).min(width as i64 - 1) {
+                        let other = &features[index(x, y)];
+                        let spatial = ((x - i).pow(2) + (y - j).pow(2)) as f32;
+                        let exponent = spatial / (2.0 * self.sigma_spatial.powi(2))
+                            + self.color_distance(center, other)
+                            + distance2(&center.normal, &other.normal)
+                                / (2.0 * self.sigma_normal.powi(2))
+                            + distance2(&center.albedo, &other.albedo)
+                                / (2.0 * self.sigma_albedo.powi(2))
+                            + (center.depth - other.depth).powi(2)
+                                / (2.0 * (self.sigma_depth * center.depth.max(1e-3)).powi(2));
+                        let weight = (-exponent).exp();
+                        sum += weight * other.irradiance.clone();
+                        weight_sum += weight;
+                    }
+                }
+
+                let irradiance = (1.0 / weight_sum) * sum;
+                let demodulation = Denoiser::demodulation(&center.albedo);
+                Pixel {
+                    r: irradiance.r * demodulation.r,
+                    g: irradiance.g * demodulation.g,
+                    b: irradiance.b * demodulation.b,
+                }
+            })
+            .collect()
+    }
+
+    // Background pixels have no albedo and are filtered as they are.
+    fn demodulation(albedo: &Pixel) -> Pixel {
+        match albedo.max_component() > 0.0 {
+            true => Pixel {
+                r: albedo.r.max(0.01),
+                g: albedo.g.max(0.01),
+                b: albedo.b.max(0.01),
+            },
+            false => Pixel {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+            },
+        }
+    }
+
+    // Squared color difference with the expected contribution of noise
+    // removed and the remainder normalized by the variance of both pixels,
+    // after Rousselle et al. (2012).
+    fn color_distance(&self, p: &Features, q: &Features) -> f32 {
+        let variance = p.variance + q.variance;
+        let noise = p.variance + p.variance.min(q.variance);
+        let d = (distance2(&p.irradiance, &q.irradiance) / 3.0 - noise).max(0.0);
+        d / (1e-4 + self.sigma_color.powi(2) * variance)
+    }
+}
+
+fn distance2(a: &Pixel, b: &Pixel) -> f32 {
+    (a.r - b.r).powi(2) + (a.g - b.g).powi(2) + (a.b - b.b).powi(2)
+}
@@ -0,0 +1,161 @@
+use crate::graphics::{color_space::srgb_encode, pixel::Pixel, tone_mapping::ToneMapping};
+use crate::math::vec3::Vec3;
+use crate::spectral::color_matching::{self, CIE_Y_INTEGRAL};
+
+// Turns linear radiance into encoded sRGB for low dynamic range output:
+// exposure, white balance, tone mapping and finally the sRGB transfer curve.
+#[derive(Clone, Debug)]
+pub struct DisplayTransform {
+    exposure: f32,
+    white_balance: Pixel,
+    tone_mapping: ToneMapping,
+}
+
+impl Default for DisplayTransform {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            white_balance: Pixel {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+            },
+            tone_mapping: ToneMapping::default(),
+        }
+    }
+}
+
+impl DisplayTransform {
+    // Exposure in stops, scaling radiance by 2^ev.
+    pub fn with_exposure(mut self, ev: f32) -> Self {
+        self.exposure = ev;
+        self
+    }
+
+    // Von Kries adaptation in RGB that maps the given color to neutral grey.
+    pub fn with_white_balance(mut self, white: Pixel) -> Self {
+        let luminance = white.luminance();
+        self.white_balance = Pixel {
+            r: luminance / white.r,
+            g: luminance / white.g,
+            b: luminance / white.b,
+        };
+        self
+    }
+
+    // White balance for a black body illuminant of the given temperature.
+    pub fn with_white_balance_temperature(self, kelvin: f32) -> Self {
+        self.with_white_balance(black_body_color(kelvin))
+    }
+
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    pub fn apply(&self, pixel: &Pixel) -> Pixel {
+        let scale = self.exposure.exp2();
+        let balanced = Pixel {
+            r: scale * self.white_balance.r * pixel.r,
+            g: scale * self.white_balance.g * pixel.g,
+            b: scale * self.white_balance.b * pixel.b,
+        };
+        let mapped = self.tone_mapping.apply(&balanced);
+        Pixel {
+            r: srgb_encode(mapped.r),
+            g: srgb_encode(mapped.g),
+            b: srgb_encode(mapped.b),
+        }
+    }
+}
+
+// Linear RGB of Planck's law integrated against the color matching functions
+// in 1 nm steps.
+fn black_body_color(kelvin: f32) -> Pixel {
+    const C: f64 = 299_792_458.0;
+    const H: f64 = 6.626_070_15e-34;
+    const KB: f64 = 1.380_649e-23;
+
+    let xyz = (360..=830).fold(Vec3::default(), |acc, lambda| {
+        let l = lambda as f64 * 1e-9;
+        let radiance =
+            2.0 * H * C * C / (l.powi(5) * ((H * C / (l * KB * kelvin as f64)).exp() - 1.0));
+        acc + radiance as f32 * color_matching::cie_xyz(lambda as f32)
+    });
+    color_matching::xyz_to_linear_srgb(&(xyz / CIE_Y_INTEGRAL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grey(value: f32) -> Pixel {
+        Pixel {
+            r: value,
+            g: value,
+            b: value,
+        }
+    }
+
+    // Logarithmically spaced from 1e-4 to 1e4.
+    fn values() -> impl Iterator<Item = f32> {
+        (0..=400).map(|k| 10f32.powf(-4.0 + 0.02 * k as f32))
+    }
+
+    #[test]
+    fn encodes_endpoints() {
+        let display = DisplayTransform::default();
+        assert_eq!(display.apply(&grey(0.0)).r, 0.0);
+        assert!((display.apply(&grey(1.0)).r - 1.0).abs() < 1e-6);
+        assert!((display.apply(&grey(10.0)).r - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn encodes_darkest_values_linearly() {
+        let display = DisplayTransform::default();
+        for value in [0.0001, 0.001, 0.002, 0.003, 0.003_130_8] {
+            let encoded = display.apply(&grey(value)).r;
+            assert!((encoded - 12.92 * value).abs() < 1e-6, "{}", value);
+        }
+        assert!(display.apply(&grey(0.01)).r < 12.92 * 0.01);
+    }
+
+    // AgX lifts mid-tones and ACES desaturates bright colors, which can raise
+    // single channels; the other operators only ever compress.
+    #[test]
+    fn tone_mapping_never_increases_values() {
+        let compressive = [
+            ToneMapping::Clamp,
+            ToneMapping::Reinhard,
+            ToneMapping::ExtendedReinhard { white: 1.0 },
+            ToneMapping::ExtendedReinhard { white: 4.0 },
+        ];
+        for tone_mapping in compressive {
+            let display = DisplayTransform::default().with_tone_mapping(tone_mapping);
+            for value in values() {
+                for pixel in [
+                    grey(value),
+                    Pixel {
+                        r: value,
+                        g: 0.2 * value,
+                        b: 0.0,
+                    },
+                ] {
+                    let mapped = display.apply(&pixel);
+                    let encoded = DisplayTransform::default().apply(&pixel);
+                    let channels = |p: &Pixel| [p.r, p.g, p.b];
+                    for (m, e) in channels(&mapped).into_iter().zip(channels(&encoded)) {
+                        assert!(m <= e + 1e-6, "{:?} at {}", tone_mapping, value);
+                    }
+                }
+            }
+        }
+
+        let display = DisplayTransform::default().with_tone_mapping(ToneMapping::AcesFilmic);
+        for value in values() {
+            let mapped = display.apply(&grey(value));
+            let encoded = DisplayTransform::default().apply(&grey(value));
+            assert!(mapped.max_component() <= encoded.r + 1e-6, "{}", value);
+        }
+    }
+}
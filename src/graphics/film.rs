@@ -0,0 +1,318 @@
+use crate::filters::filter::Filter;
+use crate::graphics::{
+    checkpoint::{self, Accumulator},
+    pixel::Pixel,
+};
+
+use std::io::{Read, Result, Write};
+
+// A contribution landing on an arbitrary raster position rather than on the
+// pixel being sampled, e.g. from light tracing.
+pub struct Splat {
+    pub raster: (f32, f32),
+    pub value: Pixel,
+}
+
+impl Splat {
+    pub fn new(raster: (f32, f32), value: Pixel) -> Self {
+        Self { raster, value }
+    }
+}
+
+pub struct Film<'a> {
+    pub width: u32,
+    pub height: u32,
+    filter: &'a dyn Filter,
+    // Filter weighted sums of the samples around every pixel and the sums of
+    // their weights.
+    samples: Vec<Pixel>,
+    weights: Vec<f32>,
+    splats: Vec<Pixel>,
+    counts: Vec<u32>,
+    // Running mean and sum of squared deviations of the sample luminance.
+    moments: Vec<(f32, f32)>,
+    total_samples: u64,
+}
+
+impl<'a> Film<'a> {
+    pub fn new(width: u32, height: u32, filter: &'a dyn Filter) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            width,
+            height,
+            filter,
+            samples: vec![Pixel::default(); size],
+            weights: vec![0.0; size],
+            splats: vec![Pixel::default(); size],
+            counts: vec![0; size],
+            moments: vec![(0.0, 0.0); size],
+            total_samples: 0,
+        }
+    }
+
+    // Statistics are kept for the pixel containing the sample, while its value
+    // is spread over all pixels within the filter radius.
+    pub fn add_sample(&mut self, raster: (f32, f32), value: Pixel) {
+        let (x, y) = raster;
+        let i = (x.max(0.0) as u32).min(self.width - 1);
+        let j = (y.max(0.0) as u32).min(self.height - 1);
+        for (index, weight) in self.footprint(x, y) {
+            self.samples[index] += weight * value.clone();
+            self.weights[index] += weight;
+        }
+
+        let index = self.index(i, j);
+        let luminance = value.luminance();
+        self.counts[index] += 1;
+        self.total_samples += 1;
+
+        let (mean, m2) = &mut self.moments[index];
+        let delta = luminance - *mean;
+        *mean += delta / self.counts[index] as f32;
+        *m2 += delta * (luminance - *mean);
+    }
+
+    // Filter weights of the pixels around a raster position. Pixel centers sit
+    // at half-integer raster positions. A center on the edge of the support
+    // belongs to the pixel on the positive side only, so that a box filter of
+    // radius 0.5 keeps every sample within its own pixel.
+    fn footprint(&self, x: f32, y: f32) -> Vec<(usize, f32)> {
+        let radius = self.filter.radius();
+        let range = |p: f32, size: u32| {
+            let lower = ((p - 0.5 - radius).floor() + 1.0).max(0.0) as i64;
+            let upper = ((p - 0.5 + radius).floor() as i64).min(size as i64 - 1);
+            lower..=upper
+        };
+
+        range(y, self.height)
+            .flat_map(|l| range(x, self.width).map(move |k| (k, l)))
+            .filter_map(|(k, l)| {
+                let weight = self.filter.evaluate(k as f32 + 0.5 - x, l as f32 + 0.5 - y);
+                (weight != 0.0).then(|| (self.index(k as u32, l as u32), weight))
+            })
+            .collect()
+    }
+
+    // Splats are spread with the same filter as camera samples. Their weights
+    // are normalized per splat rather than per pixel, as splats add up to the
+    // pixel value instead of averaging to it.
+    pub fn add_splat(&mut self, splat: Splat) {
+        let (x, y) = splat.raster;
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return;
+        }
+        let footprint = self.footprint(x, y);
+        let total: f32 = footprint.iter().map(|(_, weight)| weight).sum();
+        if total <= f32::EPSILON {
+            let index = self.index(x as u32, y as u32);
+            self.splats[index] += splat.value;
+            return;
+        }
+        for (index, weight) in footprint {
+            self.splats[index] += weight / total * splat.value.clone();
+        }
+    }
+
+    // Every camera sample traces one light subpath, so splats are averaged
+    // over the mean number of samples per pixel across the whole film.
+    pub fn pixel(&self, i: u32, j: u32) -> Pixel {
+        let index = self.index(i, j);
+        let weight = self.weights[index];
+        // Negative filter lobes can cancel the weights of sparsely sampled
+        // pixels, which are left without a filtered estimate.
+        let weight = if weight <= f32::EPSILON {
+            0.0
+        } else {
+            1.0 / weight
+        };
+        let mean_count = (self.total_samples.max(1) as f64 / self.samples.len() as f64) as f32;
+        let value =
+            weight * self.samples[index].clone() + (1.0 / mean_count) * self.splats[index].clone();
+        // Negative filter lobes can push pixels below zero.
+        Pixel {
+            r: value.r.max(0.0),
+            g: value.g.max(0.0),
+            b: value.b.max(0.0),
+        }
+    }
+
+    pub fn sample_count(&self, i: u32, j: u32) -> u32 {
+        self.counts[self.index(i, j)]
+    }
+
+    // Variance of the mean luminance of a pixel, estimated from its samples.
+    pub fn variance(&self, i: u32, j: u32) -> f32 {
+        let index = self.index(i, j);
+        let count = self.counts[index];
+        if count < 2 {
+            return 0.0;
+        }
+        self.moments[index].1 / ((count - 1) as f32 * count as f32)
+    }
+
+    // Standard error of the mean luminance relative to the mean itself. A black
+    // pixel only ever received black samples and is trivially converged.
+    pub fn relative_error(&self, i: u32, j: u32) -> f32 {
+        let index = self.index(i, j);
+        let count = self.counts[index];
+        let mean = self.moments[index].0;
+        if count < 2 {
+            return f32::INFINITY;
+        }
+        if mean <= 0.0 {
+            return 0.0;
+        }
+        self.variance(i, j).sqrt() / mean
+    }
+
+    fn index(&self, i: u32, j: u32) -> usize {
+        (j * self.width + i) as usize
+    }
+}
+
+impl Accumulator for Film<'_> {
+    fn save(&self, w: &mut dyn Write) -> Result<()> {
+        checkpoint::write_u32(w, self.width)?;
+        checkpoint::write_u32(w, self.height)?;
+        checkpoint::write_u64(w, self.total_samples)?;
+        for index in 0..self.samples.len() {
+            checkpoint::write_pixel(w, &self.samples[index])?;
+            checkpoint::write_f32(w, self.weights[index])?;
+            checkpoint::write_pixel(w, &self.splats[index])?;
+            checkpoint::write_u32(w, self.counts[index])?;
+            checkpoint::write_f32(w, self.moments[index].0)?;
+            checkpoint::write_f32(w, self.moments[index].1)?;
+        }
+        Ok(())
+    }
+
+    fn restore(&mut self, r: &mut dyn Read) -> Result<()> {
+        if checkpoint::read_u32(r)? != self.width || checkpoint::read_u32(r)? != self.height {
+            return Err(checkpoint::invalid(
+                "checkpoint resolution differs from the film",
+            ));
+        }
+        self.total_samples = checkpoint::read_u64(r)?;
+        for index in 0..self.samples.len() {
+            self.samples[index] = checkpoint::read_pixel(r)?;
+            self.weights[index] = checkpoint::read_f32(r)?;
+            self.splats[index] = checkpoint::read_pixel(r)?;
+            self.counts[index] = checkpoint::read_u32(r)?;
+            self.moments[index] = (checkpoint::read_f32(r)?, checkpoint::read_f32(r)?);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::{
+        box_filter::BoxFilter, gaussian_filter::GaussianFilter, lanczos_filter::LanczosFilter,
+        mitchell_filter::MitchellFilter, tent_filter::TentFilter,
+    };
+
+    const WIDTH: u32 = 8;
+    const HEIGHT: u32 = 6;
+
+    // Weighs nothing, so that splats have no footprint to spread over.
+    struct ZeroFilter;
+
+    impl Filter for ZeroFilter {
+        fn radius(&self) -> f32 {
+            1.0
+        }
+
+        fn evaluate(&self, _x: f32, _y: f32) -> f32 {
+            0.0
+        }
+    }
+
+    fn filters() -> Vec<Box<dyn Filter>> {
+        vec![
+            Box::new(BoxFilter::default()),
+            Box::new(TentFilter::default()),
+            Box::new(GaussianFilter::default()),
+            Box::new(MitchellFilter::default()),
+            Box::new(LanczosFilter::default()),
+        ]
+    }
+
+    fn grey(value: f32) -> Pixel {
+        Pixel {
+            r: value,
+            g: value,
+            b: value,
+        }
+    }
+
+    fn splat_energy(film: &Film) -> f32 {
+        film.splats.iter().map(|splat| splat.r).sum()
+    }
+
+    #[test]
+    fn filtered_constant_stays_constant() {
+        for filter in filters() {
+            let mut film = Film::new(WIDTH, HEIGHT, filter.as_ref());
+            let n = 8;
+            for y in 0..HEIGHT * n {
+                for x in 0..WIDTH * n {
+                    let raster = ((x as f32 + 0.5) / n as f32, (y as f32 + 0.5) / n as f32);
+                    film.add_sample(raster, grey(0.7));
+                }
+            }
+            for j in 0..HEIGHT {
+                for i in 0..WIDTH {
+                    assert!((film.pixel(i, j).r - 0.7).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn splats_conserve_energy() {
+        let positions = [
+            (3.3, 2.7),
+            (0.0, 0.0),
+            (0.01, 3.5),
+            (WIDTH as f32 - 0.01, HEIGHT as f32 - 0.01),
+        ];
+        for filter in filters() {
+            for raster in positions {
+                let mut film = Film::new(WIDTH, HEIGHT, filter.as_ref());
+                film.add_splat(Splat::new(raster, grey(2.0)));
+                assert!((splat_energy(&film) - 2.0).abs() < 1e-4, "{:?}", raster);
+            }
+        }
+    }
+
+    #[test]
+    fn splats_outside_the_film_are_dropped() {
+        let filter = TentFilter::default();
+        let mut film = Film::new(WIDTH, HEIGHT, &filter);
+        film.add_splat(Splat::new((-0.1, 1.0), grey(1.0)));
+        film.add_splat(Splat::new((1.0, HEIGHT as f32), grey(1.0)));
+        assert_eq!(splat_energy(&film), 0.0);
+    }
+
+    #[test]
+    fn splats_without_footprint_land_in_their_pixel() {
+        let filter = ZeroFilter;
+        let mut film = Film::new(WIDTH, HEIGHT, &filter);
+        film.add_splat(Splat::new((WIDTH as f32 - 0.01, 0.5), grey(1.0)));
+        assert_eq!(film.splats[film.index(WIDTH - 1, 0)].r, 1.0);
+        assert_eq!(splat_energy(&film), 1.0);
+    }
+
+    #[test]
+    fn pixels_without_positive_weight_are_black() {
+        let filter = MitchellFilter::default();
+        let mut film = Film::new(WIDTH, HEIGHT, &filter);
+        // Pixel (0, 0) is only reached by the negative lobe of the filter.
+        film.add_sample((2.0, 0.5), grey(1.0));
+        assert!(film.weights[film.index(0, 0)] < 0.0);
+        assert_eq!(film.pixel(0, 0).r, 0.0);
+        assert!(film.pixel(1, 0).r > 0.0);
+        assert_eq!(film.pixel(WIDTH - 1, HEIGHT - 1).r, 0.0);
+    }
+}
@@ -1,12 +1,14 @@
-use crate::graphics::pixel::Pixel;
+use crate::graphics::{color_space::ColorSpace, display_transform::DisplayTransform, pixel::Pixel};
 
-use std::fs::File;
-use std::io::Write;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
 
 pub struct Image {
     pub width: u32,
     pub height: u32,
     pub aspect_ratio: f32,
+    // Space of float output; 8 bit output is always sRGB.
+    pub color_space: ColorSpace,
 }
 
 impl Default for Image {
@@ -26,30 +28,112 @@ impl Image {
             width,
             height,
             aspect_ratio,
+            color_space: ColorSpace::default(),
         }
     }
 
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    // `to_pixel` yields values in `color_space`. Paths ending in ".pfm" keep
+    // them as floats converted to the image's space, anything else is written
+    // as an 8 bit sRGB PPM through the display transform. The image goes to
+    // `path.tmp` first and is then renamed, so that a progressive snapshot is
+    // never seen half written.
     pub fn write_gradient_to_file<C: Fn(u32, u32) -> Pixel>(
         &self,
         path: &str,
         to_pixel: C,
+        color_space: ColorSpace,
+        display: &DisplayTransform,
     ) -> std::io::Result<()> {
-        let mut f = File::create(path)?;
+        let temporary = format!("{}.tmp", path);
+        if path.ends_with(".pfm") {
+            self.write_pfm(&temporary, |i, j| {
+                color_space.convert(&to_pixel(i, j), self.color_space)
+            })?;
+        } else {
+            self.write_ppm(&temporary, |i, j| {
+                display.apply(&color_space.convert(&to_pixel(i, j), ColorSpace::LinearSrgb))
+            })?;
+        }
+        fs::rename(temporary, path)
+    }
+
+    fn write_ppm<C: Fn(u32, u32) -> Pixel>(&self, path: &str, to_pixel: C) -> std::io::Result<()> {
+        let mut f = BufWriter::new(File::create(path)?);
 
         write!(f, "{}", self.generate_header())?;
 
-        (0..self.height).into_iter().for_each(|j| {
-            println!("Scanlines remaining: {}", self.height - j);
-            (0..self.width).into_iter().for_each(|i| {
-                write!(f, "{}\n", to_pixel(i, j).to_string()).unwrap();
-            });
-        });
+        for j in 0..self.height {
+            for i in 0..self.width {
+                writeln!(f, "{}", to_pixel(i, j))?;
+            }
+        }
 
-        println!("Done");
-        Ok(())
+        f.flush()
+    }
+
+    // Portable float map with little endian samples, stored bottom row first.
+    fn write_pfm<C: Fn(u32, u32) -> Pixel>(&self, path: &str, to_pixel: C) -> std::io::Result<()> {
+        let mut f = BufWriter::new(File::create(path)?);
+
+        write!(f, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+
+        for j in (0..self.height).rev() {
+            for i in 0..self.width {
+                let pixel = to_pixel(i, j);
+                for value in [pixel.r, pixel.g, pixel.b] {
+                    f.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+
+        f.flush()
     }
 
     pub fn generate_header(&self) -> String {
         format!("P3\n{} {}\n255\n", self.width, self.height)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    #[test]
+    fn replaces_images_without_leaving_temporary_files() {
+        let dir = std::env::temp_dir().join(format!("image-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let image = Image::new(4, 2.0);
+        let grey = |_: u32, _: u32| Pixel {
+            r: 0.5,
+            g: 0.5,
+            b: 0.5,
+        };
+
+        for name in ["snapshot.ppm", "snapshot.pfm"] {
+            let path = dir.join(name).to_string_lossy().to_string();
+            fs::write(&path, "stale").unwrap();
+            image
+                .write_gradient_to_file(
+                    &path,
+                    grey,
+                    ColorSpace::LinearSrgb,
+                    &DisplayTransform::default(),
+                )
+                .unwrap();
+            let header = match name.ends_with(".pfm") {
+                true => "PF\n4 2\n",
+                false => "P3\n4 2\n255\n",
+            };
+            assert!(fs::read(&path).unwrap().starts_with(header.as_bytes()));
+            assert!(!Path::new(&format!("{}.tmp", path)).exists());
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,5 +1,7 @@
+use crate::graphics::color_space::ColorSpace;
 use crate::math::{interval::Interval, vec3::Vec3};
 
+use std::fmt;
 use std::ops;
 
 #[derive(Default, Debug, Clone)]
@@ -10,18 +12,12 @@ pub struct Pixel {
 }
 
 impl Pixel {
-    pub fn from_miss(direction: &Vec3) -> Pixel {
+    // The sky gradient is defined in linear sRGB and returned in `color_space`.
+    pub fn from_miss(direction: &Vec3, color_space: ColorSpace) -> Pixel {
         let unit_direction = direction.unit_vector();
         let alpha = 0.5 * (unit_direction.y + 1.0);
         let vec = (1.0 - alpha) * Vec3::new(1.0, 1.0, 1.0) + alpha * Vec3::new(0.5, 0.7, 1.0);
-        Pixel::from(&vec)
-    }
-
-    pub fn from_hit(direction: &Vec3) -> Pixel {
-        let mut n = (direction - Vec3::new(0.0, 0.0, -1.0)).unit_vector();
-        n += Vec3::new(1.0, 1.0, 1.0);
-        n *= 0.5;
-        Pixel::from(&n)
+        ColorSpace::LinearSrgb.convert(&Pixel::from(&vec), color_space)
     }
 
     pub fn normalize(self, n: u32) -> Pixel {
@@ -34,12 +30,17 @@ impl Pixel {
         }
     }
 
-    fn to_gamma2_repr(val: f32) -> f32 {
-        val.sqrt()
+    pub fn max_component(&self) -> f32 {
+        self.r.max(self.g).max(self.b)
+    }
+
+    // Rec. 709 luminance of the linear values.
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
     }
 
     fn to_8bit_repr(val: f32) -> u32 {
-        (255.999 * val) as u32
+        (255.999 * val.clamp(0.0, 1.0)) as u32
     }
 }
 
@@ -55,6 +56,16 @@ impl ops::Add for Pixel {
     }
 }
 
+impl ops::AddAssign for Pixel {
+    fn add_assign(&mut self, other: Self) {
+        *self = Self {
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
+        }
+    }
+}
+
 impl ops::Mul for Pixel {
     type Output = Pixel;
 
@@ -89,13 +100,15 @@ impl From<&Vec3> for Pixel {
     }
 }
 
-impl ToString for Pixel {
-    fn to_string(&self) -> String {
-        format!(
+// Writes display-encoded values, see `DisplayTransform`.
+impl fmt::Display for Pixel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
             "{} {} {}",
-            Pixel::to_8bit_repr(Pixel::to_gamma2_repr(self.r)),
-            Pixel::to_8bit_repr(Pixel::to_gamma2_repr(self.g)),
-            Pixel::to_8bit_repr(Pixel::to_gamma2_repr(self.b)),
+            Pixel::to_8bit_repr(self.r),
+            Pixel::to_8bit_repr(self.g),
+            Pixel::to_8bit_repr(self.b),
         )
     }
 }
@@ -0,0 +1,155 @@
+use crate::graphics::renderer::RenderResult;
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+// Snapshot of a running render. A pixel counts as done once per pass, also
+// when adaptive sampling skips it, while `rays` counts every ray traced
+// through the scene, shadow rays included. Both cover the passes of this run,
+// so a render resumed from a checkpoint starts over at zero.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    pub pass: u32,
+    pub passes: u32,
+    pub pixels_done: u64,
+    pub pixels_total: u64,
+    pub rays: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f32 {
+        match self.pixels_total {
+            0 => 1.0,
+            total => self.pixels_done as f32 / total as f32,
+        }
+    }
+
+    pub fn eta(&self) -> Option<Duration> {
+        match self.pixels_done {
+            0 => None,
+            done => Some(
+                self.elapsed
+                    .mul_f64((self.pixels_total - done) as f64 / done as f64),
+            ),
+        }
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        self.rays as f64 / self.elapsed.as_secs_f64().max(1e-9)
+    }
+}
+
+// Called by the renderer after every row of pixels and once more when the
+// render is over and all outputs have been written. Warnings are about
+// problems the render recovers from.
+pub trait ProgressObserver {
+    fn on_progress(&mut self, progress: &Progress);
+
+    fn on_warning(&mut self, _message: &str) {}
+
+    fn on_finish(&mut self, _progress: &Progress, _result: &RenderResult) {}
+}
+
+pub struct SilentProgress;
+
+impl ProgressObserver for SilentProgress {
+    fn on_progress(&mut self, _progress: &Progress) {}
+}
+
+// Redraws a single line on stderr, at most once per `interval`.
+pub struct TerminalProgress {
+    pub width: usize,
+    pub interval: Duration,
+    last_draw: Option<Instant>,
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        Self {
+            width: 40,
+            interval: Duration::from_millis(100),
+            last_draw: None,
+        }
+    }
+}
+
+impl TerminalProgress {
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    fn draw(&self, progress: &Progress) {
+        let filled = (progress.fraction() * self.width as f32) as usize;
+        let eta = progress.eta().map_or("--".to_string(), format_duration);
+        eprint!(
+            "\r[{}{}] {:3.0}% pass {}/{} {:.2} Mrays/s {} elapsed, {} left ",
+            "#".repeat(filled.min(self.width)),
+            "-".repeat(self.width - filled.min(self.width)),
+            100.0 * progress.fraction(),
+            progress.pass + 1,
+            progress.passes,
+            progress.rays_per_second() / 1e6,
+            format_duration(progress.elapsed),
+            eta
+        );
+        let _ = std::io::stderr().flush();
+    }
+}
+
+impl ProgressObserver for TerminalProgress {
+    fn on_warning(&mut self, message: &str) {
+        // Starts a new line if the bar has been drawn on the current one.
+        if self.last_draw.is_some() {
+            eprintln!();
+        }
+        eprintln!("Warning: {}", message);
+    }
+
+    fn on_progress(&mut self, progress: &Progress) {
+        if self
+            .last_draw
+            .is_none_or(|last_draw| last_draw.elapsed() >= self.interval)
+        {
+            self.draw(progress);
+            self.last_draw = Some(Instant::now());
+        }
+    }
+
+    fn on_finish(&mut self, progress: &Progress, result: &RenderResult) {
+        self.draw(progress);
+        eprintln!();
+        if result.interrupted {
+            eprintln!(
+                "Stopped after {} of {} passes",
+                result.passes, progress.passes
+            );
+        }
+        let counts = &result.sample_counts;
+        let min = counts.iter().min().copied().unwrap_or(0);
+        let max = counts.iter().max().copied().unwrap_or(0);
+        let mean = counts.iter().map(|&n| n as f64).sum::<f64>() / counts.len().max(1) as f64;
+        eprintln!(
+            "Samples per pixel: {} min, {:.1} mean, {} max",
+            min, mean, max
+        );
+        eprint!("{}", result.statistics);
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..=59 => format!("{:.1}s", duration.as_secs_f32()),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds / 60 % 60),
+    }
+}
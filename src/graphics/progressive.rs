@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+// Writes the current estimate to the output path after every pass of
+// `samples_per_pass` samples per pixel, so that an unfinished render is
+// still a usable image. With an interval, passes ending sooner than that
+// after the previous snapshot are skipped.
+pub struct Progressive {
+    pub samples_per_pass: u32,
+    pub interval: Option<Duration>,
+}
+
+impl Progressive {
+    pub fn new(samples_per_pass: u32) -> Self {
+        Self {
+            samples_per_pass: samples_per_pass.max(1),
+            interval: None,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    pub fn is_due(&self, samples: u32, since_snapshot: Duration) -> bool {
+        samples.is_multiple_of(self.samples_per_pass)
+            && self
+                .interval
+                .is_none_or(|interval| since_snapshot >= interval)
+    }
+}
@@ -1,92 +1,674 @@
-use crate::geometry::{hittable::Hittable, ray::Ray};
-use crate::graphics::{camera::Camera, image::Image, pixel::Pixel};
-use crate::materials::{material::Material, scatter::Scatter};
-use crate::math::{interval::Interval, vec3::Vec3};
+use crate::filters::{box_filter::BoxFilter, filter::Filter};
+use crate::geometry::hittable::Hittable;
+use crate::graphics::{
+    adaptive_sampling::AdaptiveSampling,
+    aov::{Aov, AovFilm},
+    camera::Camera,
+    cancellation::CancellationToken,
+    checkpoint::{Accumulator, Checkpoint, RenderIdentity},
+    color_space::ColorSpace,
+    denoiser::{Denoiser, DENOISER_FEATURES},
+    display_transform::DisplayTransform,
+    film::Film,
+    image::Image,
+    pixel::Pixel,
+    progress::{Progress, ProgressObserver, SilentProgress, TerminalProgress},
+    progressive::Progressive,
+    scene::Scene,
+    statistics::RenderStatistics,
+};
+use crate::integrators::{integrator::Integrator, path_integrator::PathIntegrator};
+use crate::math::interval::Interval;
+use crate::sampling::{independent_sampler::IndependentSampler, sampler::Sampler};
 
-use rand::Rng;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 #[derive(Default)]
 pub struct RendererBuilder {
     camera: Camera,
     image: Image,
+    integrator: Option<Box<dyn Integrator>>,
+    sampler: Option<Box<dyn Sampler>>,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    filter: Option<Box<dyn Filter>>,
+    display: DisplayTransform,
+    aovs: Vec<Aov>,
+    denoiser: Option<Denoiser>,
+    progressive: Option<Progressive>,
+    checkpoint: Option<Checkpoint>,
+    time_budget: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+    progress: Option<Box<dyn ProgressObserver>>,
+    statistics_json: Option<String>,
+    seed: u64,
 }
 
 impl RendererBuilder {
     pub fn with_default(mut self) -> Self {
         self.image = Image::default();
         self.camera = Camera::default();
+        self.integrator = None;
+        self.sampler = None;
+        self.adaptive_sampling = None;
+        self.filter = None;
+        self.display = DisplayTransform::default();
+        self.aovs = vec![];
+        self.denoiser = None;
+        self.progressive = None;
+        self.checkpoint = None;
+        self.time_budget = None;
+        self.cancellation = None;
+        self.progress = None;
+        self.statistics_json = None;
+        self.seed = 0;
+        self
+    }
+
+    pub fn with_integrator(mut self, integrator: Box<dyn Integrator>) -> Self {
+        self.integrator = Some(integrator);
+        self
+    }
+
+    pub fn with_sampler(mut self, sampler: Box<dyn Sampler>) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    // Ignored for integrators that only splat, see `Integrator::splats_only`.
+    pub fn with_adaptive_sampling(mut self, adaptive_sampling: AdaptiveSampling) -> Self {
+        self.adaptive_sampling = Some(adaptive_sampling);
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Box<dyn Filter>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn with_output_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.image = self.image.with_color_space(color_space);
+        self
+    }
+
+    pub fn with_display_transform(mut self, display: DisplayTransform) -> Self {
+        self.display = display;
+        self
+    }
+
+    // Every AOV is written next to the render as "<name>.<aov>.pfm".
+    pub fn with_aovs(mut self, aovs: Vec<Aov>) -> Self {
+        self.aovs = aovs;
+        self
+    }
+
+    pub fn with_denoiser(mut self, denoiser: Denoiser) -> Self {
+        self.denoiser = Some(denoiser);
+        self
+    }
+
+    pub fn with_progressive(mut self, progressive: Progressive) -> Self {
+        self.progressive = Some(progressive);
+        self
+    }
+
+    pub fn with_checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    // Both stop the render after the pass in flight and write what has been
+    // accumulated so far.
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    // Progress goes to a terminal progress bar unless given another observer.
+    pub fn with_progress_observer(mut self, progress: Box<dyn ProgressObserver>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_silent_progress(self) -> Self {
+        self.with_progress_observer(Box::new(SilentProgress))
+    }
+
+    pub fn with_statistics_json(mut self, path: &str) -> Self {
+        self.statistics_json = Some(path.to_string());
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
         self
     }
 
     pub fn build(self) -> Renderer {
-        Renderer::new(self.camera, self.image)
+        let integrator = self
+            .integrator
+            .unwrap_or_else(|| Box::new(PathIntegrator::new(self.camera.max_ray_bounces)));
+        let mut sampler = self
+            .sampler
+            .unwrap_or_else(|| Box::new(IndependentSampler::default()));
+        sampler.set_seed(self.seed);
+        let adaptive_sampling = self.adaptive_sampling.filter(|_| !integrator.splats_only());
+        // Samplers are sized for the most samples any pixel can receive.
+        let max_samples = adaptive_sampling
+            .as_ref()
+            .map_or(self.camera.samples_per_pixel, |a| a.max_samples);
+        sampler.set_samples_per_pixel(max_samples);
+        let mut renderer = Renderer::new(self.camera, self.image, integrator, sampler);
+        if let Some(filter) = self.filter {
+            renderer.filter = filter;
+        }
+        renderer.adaptive_sampling = adaptive_sampling;
+        renderer.display = self.display;
+        renderer.aovs = self.aovs;
+        renderer.denoiser = self.denoiser;
+        renderer.progressive = self.progressive;
+        renderer.checkpoint = self.checkpoint;
+        renderer.seed = self.seed;
+        renderer.time_budget = self.time_budget;
+        renderer.cancellation = self.cancellation;
+        if let Some(progress) = self.progress {
+            renderer.progress = progress;
+        }
+        renderer.statistics_json = self.statistics_json;
+        renderer
     }
 }
 
 pub struct Renderer {
     camera: Camera,
     image: Image,
+    integrator: Box<dyn Integrator>,
+    sampler: Box<dyn Sampler>,
+    adaptive_sampling: Option<AdaptiveSampling>,
+    filter: Box<dyn Filter>,
+    display: DisplayTransform,
+    aovs: Vec<Aov>,
+    denoiser: Option<Denoiser>,
+    progressive: Option<Progressive>,
+    checkpoint: Option<Checkpoint>,
+    // The sampler's seed, recorded in checkpoints.
+    seed: u64,
+    time_budget: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+    progress: Box<dyn ProgressObserver>,
+    statistics_json: Option<String>,
+}
+
+pub struct RenderResult {
+    pub passes: u32,
+    pub interrupted: bool,
+    // Row-major, one entry per pixel.
+    pub sample_counts: Vec<u32>,
+    pub statistics: RenderStatistics,
 }
 
 impl Renderer {
-    pub fn new(camera: Camera, image: Image) -> Self {
-        Self { camera, image }
+    pub fn new(
+        camera: Camera,
+        image: Image,
+        integrator: Box<dyn Integrator>,
+        sampler: Box<dyn Sampler>,
+    ) -> Self {
+        Self {
+            camera,
+            image,
+            integrator,
+            sampler,
+            adaptive_sampling: None,
+            filter: Box::new(BoxFilter::default()),
+            display: DisplayTransform::default(),
+            aovs: vec![],
+            denoiser: None,
+            progressive: None,
+            checkpoint: None,
+            seed: 0,
+            time_budget: None,
+            cancellation: None,
+            progress: Box::new(TerminalProgress::default()),
+            statistics_json: None,
+        }
     }
 
-    pub fn render<T: Hittable>(&self, path: &str, obj: &T) {
+    pub fn render(&mut self, path: &str, scene: &Scene) -> io::Result<RenderResult> {
+        self.integrator
+            .validate(scene)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+        let mut film = Film::new(self.image.width, self.image.height, self.filter.as_ref());
+        let mut aovs = self.aovs.clone();
+        if self.denoiser.is_some() {
+            let missing = DENOISER_FEATURES.iter().filter(|aov| !aovs.contains(aov));
+            aovs.extend(missing.collect::<Vec<_>>());
+        }
+        let mut aov_film = AovFilm::new(self.image.width, self.image.height, &aovs);
+        let mut splats = vec![];
+        let identity = RenderIdentity {
+            seed: self.seed,
+            sampler: self.sampler.name().to_string(),
+            integrator: self.integrator.name().to_string(),
+        };
+        let sampler = self.sampler.as_mut();
+        let adaptive = self.adaptive_sampling.as_ref();
+        let converged = |film: &Film, i: u32, j: u32| -> bool {
+            adaptive.is_some_and(|adaptive| adaptive.is_converged(film, i, j))
+        };
+        let max_samples = adaptive.map_or(self.camera.samples_per_pixel, |a| a.max_samples);
+        let mut last_snapshot = Instant::now();
+        let mut last_checkpoint = Instant::now();
+        let start = Instant::now();
+        let mut interrupted = false;
+        let mut render_time = Duration::ZERO;
+        scene.statistics.reset();
+
+        let first_pass = match &self.checkpoint {
+            Some(checkpoint) => match checkpoint.restore(
+                &identity,
+                &mut [&mut film, &mut aov_film, &mut self.integrator],
+            ) {
+                Ok(next_pass) => next_pass.unwrap_or(0),
+                // A truncated checkpoint or one written for other settings
+                // cannot be resumed, the render starts over with fresh films.
+                Err(e) if matches!(e.kind(), ErrorKind::InvalidData | ErrorKind::UnexpectedEof) => {
+                    self.progress
+                        .on_warning(&format!("discarding checkpoint {}: {}", checkpoint.path, e));
+                    checkpoint.remove()?;
+                    film = Film::new(self.image.width, self.image.height, self.filter.as_ref());
+                    aov_film = AovFilm::new(self.image.width, self.image.height, &aovs);
+                    0
+                }
+                Err(e) => return Err(e),
+            },
+            None => 0,
+        };
+
+        let mut passes = first_pass;
+        let mut progress = Progress {
+            pass: first_pass,
+            passes: max_samples,
+            pixels_done: 0,
+            pixels_total: (self.image.width * self.image.height) as u64
+                * max_samples.saturating_sub(first_pass) as u64,
+            rays: 0,
+            elapsed: Duration::ZERO,
+        };
+        for pass in first_pass..max_samples {
+            let active = (0..self.image.height)
+                .flat_map(|j| (0..self.image.width).map(move |i| (i, j)))
+                .filter(|&(i, j)| !converged(&film, i, j))
+                .count();
+            if active == 0 {
+                break;
+            }
+            progress.pass = pass;
+            // Work done once per pass draws from a stream of its own.
+            sampler.start_pixel_sample((u32::MAX, u32::MAX), pass);
+            let pass_start = Instant::now();
+            self.integrator
+                .begin_pass(scene, &self.camera, pass, sampler);
+
+            for j in 0..self.image.height {
+                for i in 0..self.image.width {
+                    if converged(&film, i, j) {
+                        continue;
+                    }
+                    sampler.start_pixel_sample((i, j), pass);
+                    let (u, v) = sampler.get_2d();
+                    let raster = (i as f32 + u, j as f32 + v);
+                    let ray = self.camera.generate_ray(raster, sampler);
+                    if !aov_film.is_empty() {
+                        // Bypasses the scene to keep AOVs out of the statistics.
+                        let record = scene
+                            .objects
+                            .hit(&ray, &Interval::new(0.001, f32::INFINITY));
+                        aov_film.add_sample(i, j, &ray, record.as_ref());
+                    }
+                    scene.statistics.begin_path();
+                    let radiance =
+                        self.integrator
+                            .radiance(&ray, scene, &self.camera, sampler, &mut splats);
+                    scene.statistics.end_path();
+                    film.add_sample(raster, radiance);
+                }
+                progress.pixels_done += self.image.width as u64;
+                progress.rays = scene.statistics.rays();
+                progress.elapsed = start.elapsed();
+                self.progress.on_progress(&progress);
+            }
+            splats.drain(..).for_each(|splat| film.add_splat(splat));
+            render_time += pass_start.elapsed();
+            passes = pass + 1;
+
+            let stop = self
+                .time_budget
+                .is_some_and(|budget| start.elapsed() >= budget)
+                || self
+                    .cancellation
+                    .as_ref()
+                    .is_some_and(|token| token.is_cancelled());
+            if stop && pass + 1 < max_samples {
+                interrupted = true;
+                // Keep a checkpoint around so the render can be picked up again.
+                if let Some(checkpoint) = &self.checkpoint {
+                    let state: [&dyn Accumulator; 3] = [&film, &aov_film, &self.integrator];
+                    checkpoint.save(pass + 1, &identity, &state)?;
+                }
+                break;
+            }
+
+            let snapshot_due = self
+                .progressive
+                .as_ref()
+                .is_some_and(|p| p.is_due(pass + 1, last_snapshot.elapsed()));
+            if snapshot_due && pass + 1 < max_samples {
+                let closure = |i: u32, j: u32| -> Pixel { film.pixel(i, j) };
+                self.image.write_gradient_to_file(
+                    path,
+                    closure,
+                    scene.color_space,
+                    &self.display,
+                )?;
+                last_snapshot = Instant::now();
+            }
+
+            if let Some(checkpoint) = &self.checkpoint {
+                if checkpoint.is_due(last_checkpoint.elapsed()) {
+                    let state: [&dyn Accumulator; 3] = [&film, &aov_film, &self.integrator];
+                    checkpoint.save(pass + 1, &identity, &state)?;
+                    last_checkpoint = Instant::now();
+                }
+            }
+        }
+
+        let denoised = self
+            .denoiser
+            .as_ref()
+            .map(|denoiser| denoiser.denoise(&film, &aov_film));
         let closure = |i: u32, j: u32| -> Pixel {
-            let pixel_center =
-                &self.camera.zero + i as f32 * &self.camera.du + j as f32 * &self.camera.dv;
+            match &denoised {
+                Some(pixels) => pixels[(j * self.image.width + i) as usize].clone(),
+                None => film.pixel(i, j),
+            }
+        };
+        self.image
+            .write_gradient_to_file(path, closure, scene.color_space, &self.display)?;
+
+        if let Some(map_path) = adaptive.and_then(|a| a.sample_count_map.as_deref()) {
+            let closure = |i: u32, j: u32| -> Pixel {
+                let t = film.sample_count(i, j) as f32 / max_samples as f32;
+                Pixel { r: t, g: t, b: t }
+            };
+            // Counts are data, neither converted nor display transformed.
+            let map_path = Path::new(map_path).with_extension("pfm");
+            self.image.write_gradient_to_file(
+                &map_path.to_string_lossy(),
+                closure,
+                self.image.color_space,
+                &self.display,
+            )?;
+        }
 
-            self.sample_pixel(&pixel_center, obj)
+        for aov in aov_film.aovs().filter(|aov| self.aovs.contains(aov)) {
+            // Only colors live in the scene's space, data is written as is.
+            let color_space = match aov.is_color() {
+                true => scene.color_space,
+                false => self.image.color_space,
+            };
+            let aov_path = Path::new(path).with_extension(format!("{}.pfm", aov.name()));
+            let closure = |i: u32, j: u32| -> Pixel { aov_film.pixel(aov, i, j) };
+            self.image.write_gradient_to_file(
+                &aov_path.to_string_lossy(),
+                closure,
+                color_space,
+                &self.display,
+            )?;
+        }
+
+        if !interrupted {
+            if let Some(checkpoint) = &self.checkpoint {
+                checkpoint.remove()?;
+            }
+        }
+
+        let sample_counts: Vec<u32> = (0..self.image.height)
+            .flat_map(|j| (0..self.image.width).map(move |i| (i, j)))
+            .map(|(i, j)| film.sample_count(i, j))
+            .collect();
+        let result = RenderResult {
+            passes,
+            interrupted,
+            sample_counts,
+            statistics: scene.statistics.snapshot(render_time),
         };
+        // The outputs are written by now, so a failure here only warrants a
+        // warning.
+        if let Some(json_path) = &self.statistics_json {
+            if let Err(e) = fs::write(json_path, result.statistics.to_json()) {
+                self.progress.on_warning(&format!(
+                    "failed to write statistics to {}: {}",
+                    json_path, e
+                ));
+            }
+        }
+        // Converged pixels skipped in the passes never run still count as done.
+        if !interrupted {
+            progress.pixels_done = progress.pixels_total;
+        }
+        progress.rays = scene.statistics.rays();
+        progress.elapsed = start.elapsed();
+        self.progress.on_finish(&progress, &result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::hittable_list::HittableList;
+    use crate::graphics::camera::{CameraFrame, CameraParameters};
+    use crate::integrators::metropolis_integrator::MetropolisIntegrator;
+    use crate::lights::light_list::LightList;
+    use crate::math::vec3::Vec3;
+
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
 
-        self.image.write_gradient_to_file(path, closure).unwrap();
+    const WIDTH: u32 = 16;
+    const ASPECT_RATIO: f32 = 2.0;
+
+    // A small silent render, written into its own directory under the
+    // system's temporary one.
+    fn small_builder(samples_per_pixel: u32) -> RendererBuilder {
+        let params =
+            CameraParameters::new(ASPECT_RATIO, WIDTH, 20.0, samples_per_pixel, 4, 0.0, 10.0);
+        let frame = CameraFrame::new(
+            Vec3::new(13.0, 2.0, 3.0),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let mut builder = RendererBuilder::default().with_silent_progress();
+        builder.camera = Camera::new(params, frame);
+        builder.image = Image::new(WIDTH, ASPECT_RATIO);
+        builder
     }
 
-    fn sample_pixel<T: Hittable>(&self, pixel_center: &Vec3, obj: &T) -> Pixel {
-        let p = (0..self.camera.samples_per_pixel)
-            .into_iter()
-            .fold(Pixel::default(), |acc, _| {
-                let ray = self.perturbed_ray(pixel_center);
-                acc + self.color_ray(&ray, obj, self.camera.max_ray_bounces)
-            });
+    fn output_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("renderer-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
 
-        p.normalize(self.camera.samples_per_pixel)
+    fn sky() -> Scene {
+        Scene::new(HittableList::new(), LightList::new())
     }
 
-    fn color_ray<T: Hittable>(&self, ray: &Ray, obj: &T, depth: u32) -> Pixel {
-        match obj.hit(ray, &Interval::new(0.001, std::f32::INFINITY)) {
-            Some(record) if depth > 0 => match Material::scatter(ray, &record) {
-                Some(res) => res.attenuation * self.color_ray(&res.ray, obj, depth - 1),
-                None => Pixel::default(),
+    fn read_pfm(path: &Path) -> Vec<f32> {
+        let bytes = fs::read(path).unwrap();
+        let header_end = bytes
+            .iter()
+            .enumerate()
+            .filter(|(_, &byte)| byte == b'\n')
+            .nth(2)
+            .unwrap()
+            .0;
+        bytes[header_end + 1..]
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect()
+    }
+
+    // Cancels the render during `pass` and records warnings.
+    struct CancelDuring {
+        pass: u32,
+        token: CancellationToken,
+        warnings: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl ProgressObserver for CancelDuring {
+        fn on_progress(&mut self, progress: &Progress) {
+            if progress.pass >= self.pass {
+                self.token.cancel();
+            }
+        }
+
+        fn on_warning(&mut self, message: &str) {
+            self.warnings.borrow_mut().push(message.to_string());
+        }
+    }
+
+    // Renders four samples per pixel, stopping after `stop_after` passes
+    // when given, and returns the warnings.
+    fn render_with_checkpoint(
+        integrator: Box<dyn Integrator>,
+        seed: u64,
+        stop_after: Option<u32>,
+        dir: &Path,
+    ) -> Vec<String> {
+        let checkpoint = Checkpoint::new(&dir.join("checkpoint").to_string_lossy(), Duration::MAX);
+        let token = CancellationToken::new();
+        let warnings = Rc::new(RefCell::new(vec![]));
+        let observer = CancelDuring {
+            pass: stop_after.map_or(u32::MAX, |passes| passes - 1),
+            token: token.clone(),
+            warnings: warnings.clone(),
+        };
+        let result = small_builder(4)
+            .with_integrator(integrator)
+            .with_seed(seed)
+            .with_checkpoint(checkpoint)
+            .with_cancellation(token)
+            .with_progress_observer(Box::new(observer))
+            .build()
+            .render(&dir.join("resumed.pfm").to_string_lossy(), &sky())
+            .unwrap();
+        assert_eq!(result.interrupted, stop_after.is_some());
+        warnings.take()
+    }
+
+    fn render_uninterrupted(integrator: Box<dyn Integrator>, seed: u64, dir: &Path) -> Vec<f32> {
+        let path = dir.join("uninterrupted.pfm");
+        small_builder(4)
+            .with_integrator(integrator)
+            .with_seed(seed)
+            .build()
+            .render(&path.to_string_lossy(), &sky())
+            .unwrap();
+        read_pfm(&path)
+    }
+
+    #[test]
+    fn resumed_renders_match_uninterrupted_ones() {
+        let dir = output_dir("resume");
+        let integrators: [fn() -> Box<dyn Integrator>; 2] = [
+            || Box::new(PathIntegrator::new(4)),
+            || {
+                let path = PathIntegrator::new(4);
+                Box::new(
+                    MetropolisIntegrator::new(path)
+                        .with_bootstrap_samples(256)
+                        .with_chains(8),
+                )
             },
-            _ => Pixel::from_miss(&ray.direction()),
+        ];
+
+        for integrator in integrators {
+            let expected = render_uninterrupted(integrator(), 7, &dir);
+            assert!(render_with_checkpoint(integrator(), 7, Some(2), &dir).is_empty());
+            assert!(dir.join("checkpoint").exists());
+            assert!(render_with_checkpoint(integrator(), 7, None, &dir).is_empty());
+            assert!(!dir.join("checkpoint").exists());
+            assert_eq!(read_pfm(&dir.join("resumed.pfm")), expected);
         }
+        fs::remove_dir_all(dir).unwrap();
     }
 
-    fn perturbed_ray(&self, pixel_center: &Vec3) -> Ray {
-        let perturbed_center = self.perturb(pixel_center);
-        let origin = if self.camera.defocus_angle <= 0.0 {
-            self.camera.frame.center.clone()
-        } else {
-            self.defocus_disk_sample()
+    #[test]
+    fn discards_checkpoints_of_other_settings() {
+        let dir = output_dir("checkpoint-settings");
+        let path = || Box::new(PathIntegrator::new(4));
+        let metropolis = || {
+            let path = PathIntegrator::new(4);
+            Box::new(MetropolisIntegrator::new(path).with_bootstrap_samples(256))
         };
-        let direction = perturbed_center - &origin;
-        Ray::new(origin, direction)
+
+        render_with_checkpoint(path(), 7, Some(2), &dir);
+        let warnings = render_with_checkpoint(path(), 8, None, &dir);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("different settings"));
+        assert_eq!(
+            read_pfm(&dir.join("resumed.pfm")),
+            render_uninterrupted(path(), 8, &dir)
+        );
+
+        render_with_checkpoint(path(), 7, Some(2), &dir);
+        let warnings = render_with_checkpoint(metropolis(), 7, None, &dir);
+        assert!(warnings[0].contains("different settings"));
+        fs::remove_dir_all(dir).unwrap();
     }
 
-    fn defocus_disk_sample(&self) -> Vec3 {
-        let p = Vec3::random_in_unit_disk();
-        &self.camera.frame.center
-            + p.x * &self.camera.defocus_disk.u
-            + p.y * &self.camera.defocus_disk.v
+    #[test]
+    fn splat_only_integrators_disable_adaptive_sampling() {
+        let adaptive = || AdaptiveSampling::new(4, 64, 0.05);
+        let renderer = RendererBuilder::default()
+            .with_adaptive_sampling(adaptive())
+            .build();
+        assert!(renderer.adaptive_sampling.is_some());
+
+        let integrator = MetropolisIntegrator::new(PathIntegrator::new(4));
+        let renderer = RendererBuilder::default()
+            .with_integrator(Box::new(integrator))
+            .with_adaptive_sampling(adaptive())
+            .build();
+        assert!(renderer.adaptive_sampling.is_none());
     }
 
-    fn perturb(&self, vec: &Vec3) -> Vec3 {
-        let mut rng = rand::thread_rng();
-        let pu = -0.5 + rng.gen::<f32>();
-        let pv = -0.5 + rng.gen::<f32>();
-        vec + (pu * &self.camera.du) + (pv * &self.camera.dv)
+    #[test]
+    fn writes_linear_sample_count_maps() {
+        let dir = output_dir("sample-count-map");
+        let map_path = dir.join("counts.ppm");
+        // Every sky pixel converges after the minimum of two out of eight.
+        let adaptive =
+            AdaptiveSampling::new(2, 8, 0.01).with_sample_count_map(&map_path.to_string_lossy());
+        let mut renderer = small_builder(8).with_adaptive_sampling(adaptive).build();
+        renderer
+            .render(&dir.join("image.ppm").to_string_lossy(), &sky())
+            .unwrap();
+
+        assert!(!map_path.exists());
+        let values = read_pfm(&map_path.with_extension("pfm"));
+        assert_eq!(values.len(), 3 * (WIDTH * WIDTH / 2) as usize);
+        assert!(values.iter().all(|&value| value == 0.25));
+        fs::remove_dir_all(dir).unwrap();
     }
 }
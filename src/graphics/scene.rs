@@ -0,0 +1,53 @@
+use crate::geometry::{
+    bounding_sphere::BoundingSphere, hit_record::HitRecord, hittable::Hittable,
+    hittable_list::HittableList, ray::Ray,
+};
+use crate::graphics::{color_space::ColorSpace, statistics::RayStatistics};
+use crate::lights::light_list::LightList;
+use crate::math::interval::Interval;
+
+#[derive(Default)]
+pub struct Scene {
+    pub objects: HittableList,
+    pub lights: LightList,
+    // Linear space that all colors of the scene are given in and that the
+    // film accumulates in. Spectral rendering upsamples colors as linear sRGB
+    // and is only meaningful in that space.
+    pub color_space: ColorSpace,
+    pub statistics: RayStatistics,
+}
+
+impl Scene {
+    pub fn new(objects: HittableList, lights: LightList) -> Self {
+        Self {
+            objects,
+            lights,
+            color_space: ColorSpace::default(),
+            statistics: RayStatistics::default(),
+        }
+    }
+
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    pub fn occluded(&self, ray: &Ray, distance: f32) -> bool {
+        self.statistics.record_shadow(self.objects.len(), || {
+            self.objects
+                .hit(ray, &Interval::new(0.001, distance * (1.0 - 1e-4)))
+                .is_some()
+        })
+    }
+}
+
+impl Hittable for Scene {
+    fn hit(&self, ray: &Ray, hit_interval: &Interval<f32>) -> Option<HitRecord> {
+        self.statistics
+            .record_extension(self.objects.len(), || self.objects.hit(ray, hit_interval))
+    }
+
+    fn specular_bounds(&self) -> Option<BoundingSphere> {
+        self.objects.specular_bounds()
+    }
+}
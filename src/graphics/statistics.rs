@@ -0,0 +1,266 @@
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Timing every intersection would cost about as much as many of the tests
+// it measures, so only one in this many is timed and the total is scaled up.
+const TIMED_INTERSECTION_INTERVAL: u64 = 256;
+
+thread_local! {
+    // Rays traced by the path running on this thread, None outside of one.
+    static PATH_RAYS: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+// Counters the scene updates on every ray it traces, safe to share between
+// the threads tracing into it. The first ray of a path opened with
+// `begin_path` is the primary ray, every other extension ray is secondary,
+// including those traced outside of any path such as photons. Occlusion
+// queries count as shadow rays and never extend a path.
+#[derive(Default)]
+pub struct RayStatistics {
+    primary_rays: AtomicU64,
+    secondary_rays: AtomicU64,
+    shadow_rays: AtomicU64,
+    intersection_tests: AtomicU64,
+    timed_intersections: AtomicU64,
+    timed_intersection_nanos: AtomicU64,
+    bounce_depths: Mutex<Vec<u64>>,
+}
+
+impl RayStatistics {
+    pub fn reset(&self) {
+        for counter in [
+            &self.primary_rays,
+            &self.secondary_rays,
+            &self.shadow_rays,
+            &self.intersection_tests,
+            &self.timed_intersections,
+            &self.timed_intersection_nanos,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        PATH_RAYS.set(None);
+        self.bounce_depths.lock().unwrap().clear();
+    }
+
+    pub fn begin_path(&self) {
+        PATH_RAYS.set(Some(0));
+    }
+
+    // Paths that never traced a ray, e.g. ones replayed from a Markov chain,
+    // are left out of the histogram.
+    pub fn end_path(&self) {
+        if let Some(rays @ 1..) = PATH_RAYS.take() {
+            let depth = rays as usize - 1;
+            let mut bounce_depths = self.bounce_depths.lock().unwrap();
+            if bounce_depths.len() <= depth {
+                bounce_depths.resize(depth + 1, 0);
+            }
+            bounce_depths[depth] += 1;
+        }
+    }
+
+    pub fn record_extension<T>(&self, tests: usize, hit: impl FnOnce() -> T) -> T {
+        let path_rays = PATH_RAYS.get();
+        let ray = match path_rays {
+            Some(0) => increment(&self.primary_rays),
+            _ => increment(&self.secondary_rays),
+        };
+        if let Some(rays) = path_rays {
+            PATH_RAYS.set(Some(rays + 1));
+        }
+        self.record_intersection(ray, tests, hit)
+    }
+
+    pub fn record_shadow<T>(&self, tests: usize, hit: impl FnOnce() -> T) -> T {
+        let ray = increment(&self.shadow_rays);
+        self.record_intersection(ray, tests, hit)
+    }
+
+    // `ray` numbers the ray among those of its kind.
+    fn record_intersection<T>(&self, ray: u64, tests: usize, hit: impl FnOnce() -> T) -> T {
+        self.intersection_tests
+            .fetch_add(tests as u64, Ordering::Relaxed);
+        if !ray.is_multiple_of(TIMED_INTERSECTION_INTERVAL) {
+            return hit();
+        }
+        let start = Instant::now();
+        let result = hit();
+        increment(&self.timed_intersections);
+        self.timed_intersection_nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        result
+    }
+
+    pub fn rays(&self) -> u64 {
+        [&self.primary_rays, &self.secondary_rays, &self.shadow_rays]
+            .iter()
+            .map(|counter| counter.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    // Estimated from the timed intersections.
+    fn intersection_time(&self) -> Duration {
+        let timed = self.timed_intersections.load(Ordering::Relaxed);
+        let nanos = self.timed_intersection_nanos.load(Ordering::Relaxed);
+        Duration::from_nanos(nanos).mul_f64(self.rays() as f64 / timed.max(1) as f64)
+    }
+
+    // `render_time` is the time spent in passes, shading is whatever part of
+    // it was not spent in intersection tests.
+    pub fn snapshot(&self, render_time: Duration) -> RenderStatistics {
+        let intersection_time = self.intersection_time();
+        RenderStatistics {
+            primary_rays: self.primary_rays.load(Ordering::Relaxed),
+            secondary_rays: self.secondary_rays.load(Ordering::Relaxed),
+            shadow_rays: self.shadow_rays.load(Ordering::Relaxed),
+            intersection_tests: self.intersection_tests.load(Ordering::Relaxed),
+            intersection_time,
+            shading_time: render_time.saturating_sub(intersection_time),
+            bounce_depths: self.bounce_depths.lock().unwrap().clone(),
+        }
+    }
+}
+
+// Returns the count before the increment.
+fn increment(counter: &AtomicU64) -> u64 {
+    counter.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RenderStatistics {
+    pub primary_rays: u64,
+    pub secondary_rays: u64,
+    pub shadow_rays: u64,
+    pub intersection_tests: u64,
+    pub intersection_time: Duration,
+    pub shading_time: Duration,
+    // Number of paths by the count of bounces after the primary ray.
+    pub bounce_depths: Vec<u64>,
+}
+
+impl RenderStatistics {
+    pub fn rays(&self) -> u64 {
+        self.primary_rays + self.secondary_rays + self.shadow_rays
+    }
+
+    pub fn tests_per_ray(&self) -> f64 {
+        self.intersection_tests as f64 / self.rays().max(1) as f64
+    }
+
+    // Average number of extension rays per path, primary ray included.
+    pub fn average_path_length(&self) -> f64 {
+        let paths: u64 = self.bounce_depths.iter().sum();
+        let rays: u64 = self
+            .bounce_depths
+            .iter()
+            .enumerate()
+            .map(|(depth, &count)| (depth as u64 + 1) * count)
+            .sum();
+        rays as f64 / paths.max(1) as f64
+    }
+
+    pub fn to_json(&self) -> String {
+        let bounce_depths = self
+            .bounce_depths
+            .iter()
+            .map(|count| count.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            concat!(
+                "{{\n",
+                "  \"primary_rays\": {},\n",
+                "  \"secondary_rays\": {},\n",
+                "  \"shadow_rays\": {},\n",
+                "  \"intersection_tests\": {},\n",
+                "  \"intersection_tests_per_ray\": {},\n",
+                "  \"average_path_length\": {},\n",
+                "  \"intersection_seconds\": {},\n",
+                "  \"shading_seconds\": {},\n",
+                "  \"bounce_depths\": [{}]\n",
+                "}}\n"
+            ),
+            self.primary_rays,
+            self.secondary_rays,
+            self.shadow_rays,
+            self.intersection_tests,
+            self.tests_per_ray(),
+            self.average_path_length(),
+            self.intersection_time.as_secs_f64(),
+            self.shading_time.as_secs_f64(),
+            bounce_depths
+        )
+    }
+}
+
+impl fmt::Display for RenderStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Rays: {} primary, {} secondary, {} shadow",
+            self.primary_rays, self.secondary_rays, self.shadow_rays
+        )?;
+        writeln!(f, "Intersection tests per ray: {:.1}", self.tests_per_ray())?;
+        writeln!(f, "Average path length: {:.2}", self.average_path_length())?;
+        let total = (self.intersection_time + self.shading_time).as_secs_f64();
+        writeln!(
+            f,
+            "Time: {:.2}s intersection ({:.0}%), {:.2}s shading ({:.0}%)",
+            self.intersection_time.as_secs_f64(),
+            100.0 * self.intersection_time.as_secs_f64() / total.max(1e-9),
+            self.shading_time.as_secs_f64(),
+            100.0 * self.shading_time.as_secs_f64() / total.max(1e-9)
+        )?;
+        writeln!(f, "Bounce depths:")?;
+        let paths: u64 = self.bounce_depths.iter().sum();
+        for (depth, &count) in self.bounce_depths.iter().enumerate() {
+            let fraction = count as f64 / paths.max(1) as f64;
+            writeln!(
+                f,
+                "  {:>3} {:>12} {:5.1}% {}",
+                depth,
+                count,
+                100.0 * fraction,
+                "#".repeat((40.0 * fraction).round() as usize)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::scene::Scene;
+
+    #[test]
+    fn counts_rays_traced_from_several_threads() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Scene>();
+
+        let statistics = RayStatistics::default();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        statistics.begin_path();
+                        statistics.record_extension(2, || ());
+                        statistics.record_extension(2, || ());
+                        statistics.record_shadow(2, || ());
+                        statistics.end_path();
+                    }
+                });
+            }
+        });
+
+        let snapshot = statistics.snapshot(Duration::ZERO);
+        assert_eq!(snapshot.primary_rays, 4000);
+        assert_eq!(snapshot.secondary_rays, 4000);
+        assert_eq!(snapshot.shadow_rays, 4000);
+        assert_eq!(snapshot.intersection_tests, 24000);
+        assert_eq!(snapshot.bounce_depths, vec![0, 4000]);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::graphics::pixel::Pixel;
+
+// Maps scene-referred linear RGB to display-referred linear RGB in [0, 1].
+#[derive(Clone, Copy, Debug, Default)]
+pub enum ToneMapping {
+    #[default]
+    Clamp,
+    Reinhard,
+    // Reinhard reaching white at the given luminance instead of infinity.
+    ExtendedReinhard {
+        white: f32,
+    },
+    // Stephen Hill's fit of the ACES reference rendering and sRGB output
+    // transforms.
+    AcesFilmic,
+    // Minimal AgX with the default contrast curve.
+    Agx,
+}
+
+const ACES_INPUT: [[f32; 3]; 3] = [
+    [0.59719, 0.35458, 0.04823],
+    [0.07600, 0.90834, 0.01566],
+    [0.02840, 0.13383, 0.83777],
+];
+
+const ACES_OUTPUT: [[f32; 3]; 3] = [
+    [1.60475, -0.53108, -0.07367],
+    [-0.10208, 1.10813, -0.00605],
+    [-0.00327, -0.07276, 1.07602],
+];
+
+const AGX_INSET: [[f32; 3]; 3] = [
+    [0.842_479, 0.078_433_6, 0.079_223_75],
+    [0.042_328_24, 0.878_468_6, 0.079_166_13],
+    [0.042_375_65, 0.078_433_6, 0.879_143],
+];
+
+const AGX_OUTSET: [[f32; 3]; 3] = [
+    [1.196_879, -0.098_020_88, -0.099_029_74],
+    [-0.052_896_85, 1.151_903_1, -0.098_961_18],
+    [-0.052_971_64, -0.098_043_45, 1.151_073_7],
+];
+
+const AGX_MIN_EV: f32 = -12.473_93;
+const AGX_MAX_EV: f32 = 4.026_069;
+
+impl ToneMapping {
+    pub fn apply(&self, pixel: &Pixel) -> Pixel {
+        let mapped = match self {
+            ToneMapping::Clamp => pixel.clone(),
+            ToneMapping::Reinhard => scale_luminance(pixel, |l| l / (1.0 + l)),
+            ToneMapping::ExtendedReinhard { white } => {
+                scale_luminance(pixel, |l| l * (1.0 + l / (white * white)) / (1.0 + l))
+            }
+            ToneMapping::AcesFilmic => {
+                let v = transform(&ACES_INPUT, pixel);
+                let fit = |v: f32| {
+                    (v * (v + 0.024_578_6) - 0.000_090_537)
+                        / (v * (0.983_729 * v + 0.432_951) + 0.238_081)
+                };
+                transform(
+                    &ACES_OUTPUT,
+                    &Pixel {
+                        r: fit(v.r),
+                        g: fit(v.g),
+                        b: fit(v.b),
+                    },
+                )
+            }
+            ToneMapping::Agx => {
+                let v = transform(&AGX_INSET, pixel);
+                let curve = |v: f32| {
+                    let x = (v.max(1e-10).log2().clamp(AGX_MIN_EV, AGX_MAX_EV) - AGX_MIN_EV)
+                        / (AGX_MAX_EV - AGX_MIN_EV);
+                    let x2 = x * x;
+                    let x4 = x2 * x2;
+                    15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x
+                        + 0.4298 * x2
+                        + 0.1191 * x
+                        - 0.00232
+                };
+                let v = transform(
+                    &AGX_OUTSET,
+                    &Pixel {
+                        r: curve(v.r),
+                        g: curve(v.g),
+                        b: curve(v.b),
+                    },
+                );
+                // The curve produces gamma 2.2 encoded values.
+                Pixel {
+                    r: v.r.max(0.0).powf(2.2),
+                    g: v.g.max(0.0).powf(2.2),
+                    b: v.b.max(0.0).powf(2.2),
+                }
+            }
+        };
+        Pixel {
+            r: mapped.r.clamp(0.0, 1.0),
+            g: mapped.g.clamp(0.0, 1.0),
+            b: mapped.b.clamp(0.0, 1.0),
+        }
+    }
+}
+
+fn scale_luminance<F: Fn(f32) -> f32>(pixel: &Pixel, curve: F) -> Pixel {
+    let luminance = pixel.luminance();
+    if luminance <= 0.0 {
+        return Pixel::default();
+    }
+    (curve(luminance) / luminance) * pixel.clone()
+}
+
+fn transform(m: &[[f32; 3]; 3], p: &Pixel) -> Pixel {
+    Pixel {
+        r: m[0][0] * p.r + m[0][1] * p.g + m[0][2] * p.b,
+        g: m[1][0] * p.r + m[1][1] * p.g + m[1][2] * p.b,
+        b: m[2][0] * p.r + m[2][1] * p.g + m[2][2] * p.b,
+    }
+}
@@ -0,0 +1,8 @@
+pub mod ambient_occlusion_integrator;
+pub mod bidirectional_integrator;
+pub mod integrator;
+pub mod metropolis_integrator;
+pub mod normals_integrator;
+pub mod path_integrator;
+pub mod photon_map;
+pub mod photon_mapping_integrator;
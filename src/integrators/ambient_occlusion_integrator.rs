@@ -0,0 +1,61 @@
+use crate::geometry::{hittable::Hittable, ray::Ray};
+use crate::graphics::{camera::Camera, film::Splat, pixel::Pixel, scene::Scene};
+use crate::integrators::integrator::Integrator;
+use crate::math::{interval::Interval, vec3::Vec3};
+use crate::sampling::sampler::Sampler;
+
+pub struct AmbientOcclusionIntegrator {
+    samples: u32,
+    radius: f32,
+}
+
+impl Default for AmbientOcclusionIntegrator {
+    fn default() -> Self {
+        const SAMPLES: u32 = 16;
+        const RADIUS: f32 = f32::INFINITY;
+
+        AmbientOcclusionIntegrator::new(SAMPLES, RADIUS)
+    }
+}
+
+impl AmbientOcclusionIntegrator {
+    pub fn new(samples: u32, radius: f32) -> Self {
+        Self {
+            samples: samples.max(1),
+            radius,
+        }
+    }
+}
+
+impl Integrator for AmbientOcclusionIntegrator {
+    fn name(&self) -> &'static str {
+        "ambient occlusion"
+    }
+
+    fn radiance(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        _camera: &Camera,
+        sampler: &mut dyn Sampler,
+        _splats: &mut Vec<Splat>,
+    ) -> Pixel {
+        let Some(record) = scene.hit(ray, &Interval::new(0.001, f32::INFINITY)) else {
+            return Pixel::from(&Vec3::new(1.0, 1.0, 1.0));
+        };
+
+        let unoccluded = (0..self.samples)
+            .filter(|_| {
+                let direction = &record.shading_normal + Vec3::random_unit_vector(sampler);
+                if direction.near_zero() || direction.dot(&record.normal) <= 0.0 {
+                    return false;
+                }
+                let occlusion_ray = Ray::new(record.point.clone(), direction.unit_vector());
+                !scene.occluded(&occlusion_ray, self.radius)
+            })
+            .count();
+
+        let visibility = unoccluded as f32 / self.samples as f32;
+        Pixel::from(&Vec3::new(visibility, visibility, visibility))
+    }
+}
@@ -0,0 +1,641 @@
+use crate::geometry::{hit_record::HitRecord, hittable::Hittable, ray::Ray};
+use crate::graphics::{camera::Camera, film::Splat, pixel::Pixel, scene::Scene};
+use crate::integrators::integrator::{self, Integrator};
+use crate::materials::{material::Material, scatter::Scatter};
+use crate::math::{interval::Interval, vec3::Vec3};
+use crate::sampling::sampler::Sampler;
+
+enum VertexKind {
+    Camera,
+    Light(usize),
+    // The hit together with the ray that arrived at it.
+    Surface(HitRecord, Ray),
+    // A camera subpath that left the scene in the given direction.
+    Escaped(Vec3),
+}
+
+struct Vertex {
+    kind: VertexKind,
+    point: Vec3,
+    beta: Pixel,
+    delta: bool,
+    // Area densities of sampling this vertex from its predecessor and, in the
+    // reverse direction, from its successor.
+    pdf_fwd: f32,
+    pdf_rev: f32,
+}
+
+impl Vertex {
+    fn new(kind: VertexKind, point: Vec3, beta: Pixel, pdf_fwd: f32) -> Self {
+        Self {
+            kind,
+            point,
+            beta,
+            delta: false,
+            pdf_fwd,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn normal(&self) -> Option<&Vec3> {
+        match &self.kind {
+            VertexKind::Surface(record, _) => Some(&record.normal),
+            _ => None,
+        }
+    }
+
+    fn is_connectible(&self) -> bool {
+        match &self.kind {
+            VertexKind::Camera | VertexKind::Light(_) => true,
+            VertexKind::Surface(record, _) => !record.material.is_specular(),
+            VertexKind::Escaped(_) => false,
+        }
+    }
+
+    // Converts a solid angle density of sampling `next` from this vertex into
+    // an area density at `next`.
+    fn convert_density(&self, pdf: f32, next: &Vertex) -> f32 {
+        if let VertexKind::Escaped(_) = next.kind {
+            return pdf;
+        }
+
+        let w = &next.point - &self.point;
+        let distance_squared = w.norm_squared();
+        if distance_squared <= 0.0 {
+            return 0.0;
+        }
+        let cos_theta = next
+            .normal()
+            .map_or(1.0, |n| n.dot(&w).abs() / distance_squared.sqrt());
+        pdf * cos_theta / distance_squared
+    }
+
+    // Area density at `next` of extending a subpath through this vertex. None
+    // of the sampled BSDFs depend on the direction the vertex was reached from.
+    fn pdf(&self, scene: &Scene, camera: &Camera, next: &Vertex) -> f32 {
+        let direction = (&next.point - &self.point).unit_vector();
+        let pdf = match &self.kind {
+            VertexKind::Camera => camera.pdf_direction(&self.point, &direction),
+            VertexKind::Light(index) => scene
+                .lights
+                .get(*index)
+                .map_or(0.0, |light| light.pdf_le(&direction)),
+            VertexKind::Surface(record, ray) => Material::pdf(ray, record, &direction),
+            VertexKind::Escaped(_) => 0.0,
+        };
+        self.convert_density(pdf, next)
+    }
+
+    fn f(&self, next: &Vertex, adjoint: bool) -> Pixel {
+        self.f_towards(&(&next.point - &self.point), adjoint)
+    }
+
+    // BSDF times cosine towards `direction`. Light subpaths carry importance
+    // and need the adjoint correction for shading normals.
+    fn f_towards(&self, direction: &Vec3, adjoint: bool) -> Pixel {
+        let VertexKind::Surface(record, ray) = &self.kind else {
+            return Pixel::default();
+        };
+
+        let f = Material::eval(ray, record, direction);
+        match adjoint {
+            true => shading_correction(record, &-ray.direction(), direction) * f,
+            false => f,
+        }
+    }
+}
+
+fn shading_correction(record: &HitRecord, wo: &Vec3, wi: &Vec3) -> f32 {
+    let wo = wo.unit_vector();
+    let wi = wi.unit_vector();
+    let numerator = wo.dot(&record.shading_normal).abs() * wi.dot(&record.normal).abs();
+    let denominator = wo.dot(&record.normal).abs() * wi.dot(&record.shading_normal).abs();
+    match denominator > 0.0 {
+        true => numerator / denominator,
+        false => 0.0,
+    }
+}
+
+fn select_light(lights: &[usize], u: f32) -> usize {
+    lights[((u * lights.len() as f32) as usize).min(lights.len() - 1)]
+}
+
+fn unoccluded(scene: &Scene, from: &Vec3, to: &Vec3) -> bool {
+    let w = to - from;
+    let distance = w.norm();
+    !scene.occluded(&Ray::new(from.clone(), w / distance), distance)
+}
+
+// Bidirectional path tracing after Veach (1997): camera and light subpaths
+// are connected in every possible way and the strategies are combined with
+// the balance heuristic. Connections to the camera are splatted into the film.
+//
+// Only finite lights start light subpaths. Lights at infinity and the
+// background can only be reached by shadow rays and escaping camera paths
+// respectively, so those contributions are added unweighted.
+pub struct BidirectionalIntegrator {
+    max_depth: u32,
+}
+
+impl BidirectionalIntegrator {
+    pub fn new(max_depth: u32) -> Self {
+        Self { max_depth }
+    }
+
+    fn finite_lights(scene: &Scene) -> Vec<usize> {
+        scene
+            .lights
+            .iter()
+            .enumerate()
+            .filter(|(_, light)| !light.is_infinite())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn camera_subpath(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        camera: &Camera,
+        sampler: &mut dyn Sampler,
+    ) -> Vec<Vertex> {
+        let origin = ray.origin().clone();
+        let pdf = camera.pdf_direction(&origin, ray.direction());
+        let beta = Pixel::from(&Vec3::new(1.0, 1.0, 1.0));
+        let mut path = vec![Vertex::new(VertexKind::Camera, origin, beta.clone(), 1.0)];
+
+        let max_vertices = self.max_depth as usize + 2;
+        self.random_walk(
+            scene,
+            ray.clone(),
+            beta,
+            pdf,
+            max_vertices,
+            false,
+            &mut path,
+            sampler,
+        );
+        path
+    }
+
+    fn light_subpath(
+        &self,
+        scene: &Scene,
+        lights: &[usize],
+        sampler: &mut dyn Sampler,
+    ) -> Vec<Vertex> {
+        if lights.is_empty() {
+            return vec![];
+        }
+
+        let index = select_light(lights, sampler.get_1d());
+        let pmf = 1.0 / lights.len() as f32;
+        let Some(emission) = scene
+            .lights
+            .get(index)
+            .and_then(|light| light.sample_le(sampler))
+        else {
+            return vec![];
+        };
+
+        let origin = emission.ray.origin().clone();
+        let mut path = vec![Vertex::new(
+            VertexKind::Light(index),
+            origin,
+            emission.intensity.clone(),
+            pmf,
+        )];
+        if emission.pdf_direction <= 0.0 {
+            return path;
+        }
+
+        let beta = 1.0 / (pmf * emission.pdf_direction) * emission.intensity;
+        let max_vertices = self.max_depth as usize + 1;
+        self.random_walk(
+            scene,
+            emission.ray,
+            beta,
+            emission.pdf_direction,
+            max_vertices,
+            true,
+            &mut path,
+            sampler,
+        );
+        path
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn random_walk(
+        &self,
+        scene: &Scene,
+        mut ray: Ray,
+        mut beta: Pixel,
+        mut pdf_fwd: f32,
+        max_vertices: usize,
+        adjoint: bool,
+        path: &mut Vec<Vertex>,
+        sampler: &mut dyn Sampler,
+    ) {
+        while path.len() < max_vertices {
+            let Some(record) = scene.hit(&ray, &Interval::new(0.001, f32::INFINITY)) else {
+                if !adjoint {
+                    let direction = ray.direction().unit_vector();
+                    let origin = ray.origin().clone();
+                    path.push(Vertex::new(
+                        VertexKind::Escaped(direction),
+                        origin,
+                        beta,
+                        pdf_fwd,
+                    ));
+                }
+                break;
+            };
+
+            let point = record.point.clone();
+            let mut vertex = Vertex::new(VertexKind::Surface(record, ray), point, beta, 0.0);
+            vertex.pdf_fwd = path[path.len() - 1].convert_density(pdf_fwd, &vertex);
+            path.push(vertex);
+            if path.len() >= max_vertices {
+                break;
+            }
+
+            let n = path.len();
+            let vertex = &path[n - 1];
+            let VertexKind::Surface(record, incoming) = &vertex.kind else {
+                break;
+            };
+            let Some(res) = Material::scatter(incoming, record, sampler) else {
+                break;
+            };
+
+            let specular = record.material.is_specular();
+            let wo = -incoming.direction();
+            let (pdf_rev, pdf) = match specular {
+                true => (0.0, 0.0),
+                false => {
+                    let reverse = Ray::new(record.point.clone(), -res.ray.direction());
+                    (
+                        Material::pdf(&reverse, record, &wo),
+                        Material::pdf(incoming, record, res.ray.direction()),
+                    )
+                }
+            };
+            if !specular && pdf <= 0.0 {
+                break;
+            }
+
+            beta = vertex.beta.clone() * res.attenuation;
+            if adjoint {
+                beta = shading_correction(record, &wo, res.ray.direction()) * beta;
+            }
+            pdf_fwd = pdf;
+
+            path[n - 1].delta = specular;
+            path[n - 2].pdf_rev = path[n - 1].convert_density(pdf_rev, &path[n - 2]);
+            ray = res.ray;
+        }
+    }
+
+    // Contribution of the strategy using s light and t > 1 camera vertices.
+    #[allow(clippy::too_many_arguments)]
+    fn connect(
+        &self,
+        scene: &Scene,
+        camera: &Camera,
+        lights: &[usize],
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+        s: usize,
+        sampler: &mut dyn Sampler,
+    ) -> Pixel {
+        let t = camera_path.len();
+        let pt = &camera_path[t - 1];
+
+        match s {
+            0 => match &pt.kind {
+                VertexKind::Escaped(direction) => {
+                    pt.beta.clone() * Pixel::from_miss(direction, scene.color_space)
+                }
+                _ => Pixel::default(),
+            },
+            1 => {
+                if !pt.is_connectible() || lights.is_empty() {
+                    return Pixel::default();
+                }
+
+                let index = select_light(lights, sampler.get_1d());
+                let pmf = 1.0 / lights.len() as f32;
+                let Some(sample) = scene
+                    .lights
+                    .get(index)
+                    .and_then(|light| light.sample_li(&pt.point))
+                else {
+                    return Pixel::default();
+                };
+
+                let point = &pt.point + sample.distance * &sample.direction;
+                let beta = 1.0 / pmf * sample.radiance;
+                let sampled = Vertex::new(VertexKind::Light(index), point, beta, pmf);
+
+                let contribution = pt.beta.clone() * pt.f(&sampled, false) * sampled.beta.clone();
+                if contribution.max_component() <= 0.0
+                    || !unoccluded(scene, &pt.point, &sampled.point)
+                {
+                    return Pixel::default();
+                }
+
+                let light_path = std::slice::from_ref(&sampled);
+                self.mis_weight(scene, camera, light_path, camera_path) * contribution
+            }
+            _ => {
+                let qs = &light_path[s - 1];
+                if !qs.is_connectible() || !pt.is_connectible() {
+                    return Pixel::default();
+                }
+
+                let distance_squared = (&pt.point - &qs.point).norm_squared();
+                let contribution = 1.0 / distance_squared
+                    * qs.beta.clone()
+                    * qs.f(pt, true)
+                    * pt.f(qs, false)
+                    * pt.beta.clone();
+                if contribution.max_component() <= 0.0 || !unoccluded(scene, &qs.point, &pt.point) {
+                    return Pixel::default();
+                }
+
+                self.mis_weight(scene, camera, &light_path[..s], camera_path) * contribution
+            }
+        }
+    }
+
+    // Connects the last light vertex directly to the lens.
+    fn connect_to_camera(
+        &self,
+        scene: &Scene,
+        camera: &Camera,
+        light_path: &[Vertex],
+        sampler: &mut dyn Sampler,
+    ) -> Option<Splat> {
+        let qs = light_path.last()?;
+        if !qs.is_connectible() {
+            return None;
+        }
+        if let VertexKind::Light(_) = qs.kind {
+            return None;
+        }
+
+        let sample = camera.sample_wi(&qs.point, sampler)?;
+        if sample.importance <= 0.0 || sample.pdf <= 0.0 {
+            return None;
+        }
+
+        let point = &qs.point + sample.distance * &sample.direction;
+        let beta = Pixel::from(&Vec3::new(1.0, 1.0, 1.0));
+        let sampled = Vertex::new(
+            VertexKind::Camera,
+            point,
+            sample.importance / sample.pdf * beta,
+            0.0,
+        );
+
+        let contribution = qs.beta.clone() * qs.f(&sampled, true) * sampled.beta.clone();
+        if contribution.max_component() <= 0.0 || !unoccluded(scene, &qs.point, &sampled.point) {
+            return None;
+        }
+
+        let camera_path = std::slice::from_ref(&sampled);
+        let weight = self.mis_weight(scene, camera, light_path, camera_path);
+        Some(Splat::new(sample.raster, weight * contribution))
+    }
+
+    // Balance heuristic weight of the strategy connecting the last vertices of
+    // both subpaths, found by walking the hypothetical strategies that would
+    // have produced the same path.
+    fn mis_weight(
+        &self,
+        scene: &Scene,
+        camera: &Camera,
+        light_path: &[Vertex],
+        camera_path: &[Vertex],
+    ) -> f32 {
+        let (s, t) = (light_path.len(), camera_path.len());
+        if s + t == 2 {
+            return 1.0;
+        }
+
+        let qs = &light_path[s - 1];
+        let pt = &camera_path[t - 1];
+
+        let densities = |path: &[Vertex]| -> Vec<(f32, f32, bool)> {
+            path.iter()
+                .map(|v| (v.pdf_fwd, v.pdf_rev, v.delta))
+                .collect()
+        };
+        let mut light = densities(light_path);
+        let mut cam = densities(camera_path);
+
+        // The connection vertices are sampled towards each other.
+        light[s - 1].2 = false;
+        cam[t - 1].2 = false;
+        cam[t - 1].1 = qs.pdf(scene, camera, pt);
+        if t > 1 {
+            cam[t - 2].1 = pt.pdf(scene, camera, &camera_path[t - 2]);
+        }
+        light[s - 1].1 = pt.pdf(scene, camera, qs);
+        if s > 1 {
+            light[s - 2].1 = qs.pdf(scene, camera, &light_path[s - 2]);
+        }
+
+        let remap = |pdf: f32| match pdf != 0.0 {
+            true => pdf,
+            false => 1.0,
+        };
+
+        let mut sum = 0.0;
+        let mut ratio = 1.0;
+        for i in (1..t).rev() {
+            ratio *= remap(cam[i].1) / remap(cam[i].0);
+            if !cam[i].2 && !cam[i - 1].2 {
+                sum += ratio;
+            }
+        }
+
+        // Finite lights are points, which camera subpaths can never hit.
+        ratio = 1.0;
+        for i in (0..s).rev() {
+            ratio *= remap(light[i].1) / remap(light[i].0);
+            let delta_light = i == 0 || light[i - 1].2;
+            if !light[i].2 && !delta_light {
+                sum += ratio;
+            }
+        }
+
+        1.0 / (1.0 + sum)
+    }
+
+    fn infinite_lights(&self, scene: &Scene, pt: &Vertex) -> Pixel {
+        if !pt.is_connectible() {
+            return Pixel::default();
+        }
+
+        scene
+            .lights
+            .iter()
+            .filter(|light| light.is_infinite())
+            .filter_map(|light| light.sample_li(&pt.point))
+            .filter_map(|sample| {
+                let shadow_ray = Ray::new(pt.point.clone(), sample.direction.clone());
+                match scene.occluded(&shadow_ray, sample.distance) {
+                    true => None,
+                    false => Some(pt.f_towards(&sample.direction, false) * sample.radiance),
+                }
+            })
+            .fold(Pixel::default(), |acc, contribution| acc + contribution)
+    }
+}
+
+impl Integrator for BidirectionalIntegrator {
+    fn name(&self) -> &'static str {
+        "bidirectional path tracing"
+    }
+
+    fn validate(&self, scene: &Scene) -> Result<(), String> {
+        integrator::validate_inverse_square_falloff(scene)
+    }
+
+    fn radiance(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        camera: &Camera,
+        sampler: &mut dyn Sampler,
+        splats: &mut Vec<Splat>,
+    ) -> Pixel {
+        let lights = BidirectionalIntegrator::finite_lights(scene);
+        let camera_path = self.camera_subpath(ray, scene, camera, sampler);
+        let light_path = self.light_subpath(scene, &lights, sampler);
+
+        let max_depth = self.max_depth as usize;
+        let mut radiance = Pixel::default();
+
+        for t in 1..=camera_path.len() {
+            for s in 0..=light_path.len().max(1) {
+                if (s == 1 && t == 1) || s + t < 2 || s + t - 2 > max_depth {
+                    continue;
+                }
+
+                match t {
+                    1 => {
+                        if let Some(splat) =
+                            self.connect_to_camera(scene, camera, &light_path[..s], sampler)
+                        {
+                            splats.push(splat);
+                        }
+                    }
+                    _ => {
+                        radiance += self.connect(
+                            scene,
+                            camera,
+                            &lights,
+                            &light_path,
+                            &camera_path[..t],
+                            s,
+                            sampler,
+                        );
+                    }
+                }
+            }
+
+            if t >= 2 && t - 1 <= max_depth {
+                let pt = &camera_path[t - 1];
+                radiance += pt.beta.clone() * self.infinite_lights(scene, pt);
+            }
+        }
+
+        radiance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{hittable_list::HittableList, sphere::Sphere};
+    use crate::graphics::camera::{CameraFrame, CameraParameters};
+    use crate::integrators::path_integrator::PathIntegrator;
+    use crate::lights::{light_list::LightList, point_light::PointLight};
+    use crate::sampling::independent_sampler::IndependentSampler;
+
+    const WIDTH: u32 = 16;
+    const SAMPLES_PER_PIXEL: u32 = 64;
+
+    fn diffuse_scene() -> Scene {
+        let gray = Pixel::from(&Vec3::new(0.5, 0.5, 0.5));
+        let mut objects = HittableList::new();
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Material::Lambertian(gray.clone()),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            Material::Lambertian(gray),
+        )));
+        let mut lights = LightList::new();
+        lights.push(Box::new(PointLight::new(
+            Vec3::new(2.0, 4.0, 2.0),
+            Pixel::from(&Vec3::new(10.0, 10.0, 10.0)),
+        )));
+        Scene::new(objects, lights)
+    }
+
+    fn camera() -> Camera {
+        let params = CameraParameters::new(1.0, WIDTH, 60.0, SAMPLES_PER_PIXEL, 8, 0.0, 6.0);
+        let frame = CameraFrame::new(
+            Vec3::new(0.0, 3.0, 5.0),
+            Vec3::new(0.0, 0.5, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        Camera::new(params, frame)
+    }
+
+    // Camera samples and splats are both averaged over the samples taken per
+    // pixel, so their sum over the image gives its mean.
+    fn mean_luminance(integrator: &dyn Integrator, scene: &Scene, camera: &Camera) -> f32 {
+        let mut sampler = IndependentSampler::new(7);
+        let mut splats = vec![];
+        let mut total = 0.0;
+        for y in 0..camera.image_height {
+            for x in 0..camera.image_width {
+                for index in 0..SAMPLES_PER_PIXEL {
+                    sampler.start_pixel_sample((x, y), index);
+                    let (dx, dy) = sampler.get_2d();
+                    let ray = camera.generate_ray((x as f32 + dx, y as f32 + dy), &mut sampler);
+                    total += integrator
+                        .radiance(&ray, scene, camera, &mut sampler, &mut splats)
+                        .luminance();
+                }
+            }
+        }
+        total += splats
+            .iter()
+            .map(|splat| splat.value.luminance())
+            .sum::<f32>();
+        let samples = camera.image_width * camera.image_height * SAMPLES_PER_PIXEL;
+        total / samples as f32
+    }
+
+    #[test]
+    fn matches_path_tracer_on_diffuse_scene() {
+        let scene = diffuse_scene();
+        let camera = camera();
+        let bidirectional = mean_luminance(&BidirectionalIntegrator::new(8), &scene, &camera);
+        let path = mean_luminance(&PathIntegrator::new(8), &scene, &camera);
+        assert!(
+            (bidirectional - path).abs() < 0.03 * path,
+            "bidirectional {} vs path {}",
+            bidirectional,
+            path
+        );
+    }
+}
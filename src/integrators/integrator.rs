@@ -0,0 +1,80 @@
+use crate::geometry::ray::Ray;
+use crate::graphics::checkpoint::Accumulator;
+use crate::graphics::{camera::Camera, film::Splat, pixel::Pixel, scene::Scene};
+use crate::sampling::sampler::Sampler;
+
+use std::io::{Read, Write};
+
+pub trait Integrator {
+    // Identifies the integrator in checkpoints.
+    fn name(&self) -> &'static str;
+
+    // Called before every pass over the image, each of which takes one sample
+    // per pixel.
+    fn begin_pass(
+        &mut self,
+        _scene: &Scene,
+        _camera: &Camera,
+        _pass: u32,
+        _sampler: &mut dyn Sampler,
+    ) {
+    }
+
+    // Rejects scenes the integrator cannot render correctly before any work
+    // is done.
+    fn validate(&self, _scene: &Scene) -> Result<(), String> {
+        Ok(())
+    }
+
+    // Whether every contribution is splatted and `radiance` always returns
+    // zero, which leaves nothing to estimate per-pixel errors from.
+    fn splats_only(&self) -> bool {
+        false
+    }
+
+    // State carried from one pass to the next, which a checkpoint has to
+    // save for a resumed render to continue where it stopped.
+    fn save_state(&self, _w: &mut dyn Write) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn restore_state(&mut self, _r: &mut dyn Read) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    // Returns the radiance along a camera ray. Contributions to other pixels
+    // are pushed onto `splats`.
+    fn radiance(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        camera: &Camera,
+        sampler: &mut dyn Sampler,
+        splats: &mut Vec<Splat>,
+    ) -> Pixel;
+}
+
+impl Accumulator for Box<dyn Integrator> {
+    fn save(&self, w: &mut dyn Write) -> std::io::Result<()> {
+        self.save_state(w)
+    }
+
+    fn restore(&mut self, r: &mut dyn Read) -> std::io::Result<()> {
+        self.restore_state(r)
+    }
+}
+
+// For integrators that trace light subpaths, see `Light::falloff`.
+pub fn validate_inverse_square_falloff(scene: &Scene) -> Result<(), String> {
+    match scene
+        .lights
+        .iter()
+        .find(|light| !light.is_infinite() && light.falloff() != 2.0)
+    {
+        Some(light) => Err(format!(
+            "lights with a falloff exponent of {} cannot be traced, only 2 is supported",
+            light.falloff()
+        )),
+        None => Ok(()),
+    }
+}
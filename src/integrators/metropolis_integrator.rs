@@ -0,0 +1,246 @@
+use crate::geometry::ray::Ray;
+use crate::graphics::{
+    camera::Camera,
+    checkpoint::{self, Accumulator},
+    film::Splat,
+    pixel::Pixel,
+    scene::Scene,
+};
+use crate::integrators::{integrator::Integrator, path_integrator::PathIntegrator};
+use crate::sampling::{metropolis_sampler::MetropolisSampler, sampler::Sampler};
+
+use std::cell::{Cell, RefCell};
+use std::io::{Read, Result, Write};
+
+struct MarkovChain {
+    sampler: MetropolisSampler,
+    radiance: Pixel,
+    raster: (f32, f32),
+}
+
+// Primary sample space Metropolis light transport (Kelemen et al. 2002) on
+// top of the path tracer. A path is a point in the unit hypercube of random
+// numbers it consumes, starting with its raster position, and chains mutate
+// these numbers with a density proportional to the luminance of the path.
+//
+// The first pass bootstraps the normalization, i.e. the average luminance
+// over the image, and seeds the chains from the bootstrap paths. Every
+// sample then advances one chain and splats both the proposed and current
+// path weighted by the acceptance probability, so the camera ray handed to
+// the integrator is ignored.
+pub struct MetropolisIntegrator {
+    path: PathIntegrator,
+    bootstrap_samples: u32,
+    chain_count: u32,
+    sigma: f32,
+    large_step_probability: f32,
+    normalization: f32,
+    chains: Vec<RefCell<MarkovChain>>,
+    next_chain: Cell<usize>,
+}
+
+impl MetropolisIntegrator {
+    pub fn new(path: PathIntegrator) -> Self {
+        const BOOTSTRAP_SAMPLES: u32 = 100_000;
+        const CHAIN_COUNT: u32 = 1000;
+        const SIGMA: f32 = 0.01;
+        const LARGE_STEP_PROBABILITY: f32 = 0.3;
+
+        Self {
+            path,
+            bootstrap_samples: BOOTSTRAP_SAMPLES,
+            chain_count: CHAIN_COUNT,
+            sigma: SIGMA,
+            large_step_probability: LARGE_STEP_PROBABILITY,
+            normalization: 0.0,
+            chains: vec![],
+            next_chain: Cell::new(0),
+        }
+    }
+
+    pub fn with_bootstrap_samples(mut self, samples: u32) -> Self {
+        self.bootstrap_samples = samples;
+        self
+    }
+
+    pub fn with_chains(mut self, chains: u32) -> Self {
+        self.chain_count = chains;
+        self
+    }
+
+    // Standard deviation of small step mutations in primary sample space.
+    pub fn with_sigma(mut self, sigma: f32) -> Self {
+        self.sigma = sigma;
+        self
+    }
+
+    pub fn with_large_step_probability(mut self, probability: f32) -> Self {
+        self.large_step_probability = probability;
+        self
+    }
+
+    fn evaluate(
+        &self,
+        scene: &Scene,
+        camera: &Camera,
+        sampler: &mut MetropolisSampler,
+    ) -> (Pixel, (f32, f32)) {
+        let (u, v) = sampler.get_2d();
+        let raster = (
+            u * camera.image_width as f32,
+            v * camera.image_height as f32,
+        );
+        let ray = camera.generate_ray(raster, sampler);
+        let radiance = self
+            .path
+            .radiance(&ray, scene, camera, sampler, &mut vec![]);
+        (radiance, raster)
+    }
+
+    fn bootstrap(&mut self, scene: &Scene, camera: &Camera, sampler: &mut dyn Sampler) {
+        let seed = ((sampler.get_1d() * (1u32 << 24) as f32) as u64) << 32;
+        let new_sampler = |index: u64| {
+            MetropolisSampler::new(seed + index, self.sigma, self.large_step_probability)
+        };
+
+        let weights: Vec<f32> = (0..self.bootstrap_samples as u64)
+            .map(|index| {
+                let (radiance, _) = self.evaluate(scene, camera, &mut new_sampler(index));
+                radiance.luminance().max(0.0)
+            })
+            .collect();
+        let total: f32 = weights.iter().sum();
+        self.normalization = total / self.bootstrap_samples.max(1) as f32;
+        if total <= 0.0 {
+            return;
+        }
+
+        self.chains = (0..self.chain_count)
+            .map(|_| {
+                let target = sampler.get_1d() * total;
+                let mut sum = 0.0;
+                let index = weights
+                    .iter()
+                    .position(|weight| {
+                        sum += weight;
+                        sum > target
+                    })
+                    .unwrap_or(weights.len() - 1);
+
+                let mut chain_sampler = new_sampler(index as u64);
+                let (radiance, raster) = self.evaluate(scene, camera, &mut chain_sampler);
+                RefCell::new(MarkovChain {
+                    sampler: chain_sampler,
+                    radiance,
+                    raster,
+                })
+            })
+            .collect();
+    }
+}
+
+impl Integrator for MetropolisIntegrator {
+    fn name(&self) -> &'static str {
+        "metropolis"
+    }
+
+    fn splats_only(&self) -> bool {
+        true
+    }
+
+    fn begin_pass(
+        &mut self,
+        scene: &Scene,
+        camera: &Camera,
+        _pass: u32,
+        sampler: &mut dyn Sampler,
+    ) {
+        if self.chains.is_empty() {
+            self.bootstrap(scene, camera, sampler);
+        }
+    }
+
+    fn save_state(&self, w: &mut dyn Write) -> Result<()> {
+        checkpoint::write_f32(w, self.normalization)?;
+        checkpoint::write_u64(w, self.next_chain.get() as u64)?;
+        checkpoint::write_u64(w, self.chains.len() as u64)?;
+        for chain in &self.chains {
+            let chain = chain.borrow();
+            chain.sampler.save(w)?;
+            checkpoint::write_pixel(w, &chain.radiance)?;
+            checkpoint::write_f32(w, chain.raster.0)?;
+            checkpoint::write_f32(w, chain.raster.1)?;
+        }
+        Ok(())
+    }
+
+    // Leaves the chains untouched unless all of them could be read.
+    fn restore_state(&mut self, r: &mut dyn Read) -> Result<()> {
+        let normalization = checkpoint::read_f32(r)?;
+        let next_chain = checkpoint::read_u64(r)? as usize;
+        let len = checkpoint::read_u64(r)?;
+        let chains = (0..len)
+            .map(|_| {
+                let mut sampler =
+                    MetropolisSampler::new(0, self.sigma, self.large_step_probability);
+                sampler.restore(r)?;
+                Ok(RefCell::new(MarkovChain {
+                    sampler,
+                    radiance: checkpoint::read_pixel(r)?,
+                    raster: (checkpoint::read_f32(r)?, checkpoint::read_f32(r)?),
+                }))
+            })
+            .collect::<Result<_>>()?;
+        self.normalization = normalization;
+        self.next_chain.set(next_chain);
+        self.chains = chains;
+        Ok(())
+    }
+
+    fn radiance(
+        &self,
+        _ray: &Ray,
+        scene: &Scene,
+        camera: &Camera,
+        sampler: &mut dyn Sampler,
+        splats: &mut Vec<Splat>,
+    ) -> Pixel {
+        if self.chains.is_empty() {
+            return Pixel::default();
+        }
+
+        let index = self.next_chain.get() % self.chains.len();
+        self.next_chain.set(index + 1);
+        let mut chain = self.chains[index].borrow_mut();
+
+        chain.sampler.start_iteration();
+        let (proposed, raster) = self.evaluate(scene, camera, &mut chain.sampler);
+        let proposed_luminance = proposed.luminance();
+        let current_luminance = chain.radiance.luminance();
+
+        let accept = match current_luminance > 0.0 {
+            true => (proposed_luminance / current_luminance).clamp(0.0, 1.0),
+            false => 1.0,
+        };
+
+        if proposed_luminance > 0.0 {
+            let weight = accept * self.normalization / proposed_luminance;
+            splats.push(Splat::new(raster, weight * proposed.clone()));
+        }
+        if current_luminance > 0.0 {
+            let weight = (1.0 - accept) * self.normalization / current_luminance;
+            splats.push(Splat::new(chain.raster, weight * chain.radiance.clone()));
+        }
+
+        match sampler.get_1d() < accept {
+            true => {
+                chain.radiance = proposed;
+                chain.raster = raster;
+                chain.sampler.accept();
+            }
+            false => chain.sampler.reject(),
+        }
+
+        Pixel::default()
+    }
+}
@@ -0,0 +1,30 @@
+use crate::geometry::{hittable::Hittable, ray::Ray};
+use crate::graphics::{camera::Camera, film::Splat, pixel::Pixel, scene::Scene};
+use crate::integrators::integrator::Integrator;
+use crate::math::{interval::Interval, vec3::Vec3};
+use crate::sampling::sampler::Sampler;
+
+#[derive(Default)]
+pub struct NormalsIntegrator;
+
+impl Integrator for NormalsIntegrator {
+    fn name(&self) -> &'static str {
+        "normals"
+    }
+
+    fn radiance(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        _camera: &Camera,
+        _sampler: &mut dyn Sampler,
+        _splats: &mut Vec<Splat>,
+    ) -> Pixel {
+        match scene.hit(ray, &Interval::new(0.001, f32::INFINITY)) {
+            Some(record) => {
+                Pixel::from(&(0.5 * (record.shading_normal + Vec3::new(1.0, 1.0, 1.0))))
+            }
+            None => Pixel::default(),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::geometry::{hit_record::HitRecord, hittable::Hittable, ray::Ray};
+use crate::graphics::{camera::Camera, film::Splat, pixel::Pixel, scene::Scene};
+use crate::integrators::integrator::Integrator;
+use crate::materials::{material::Material, scatter::Scatter};
+use crate::math::{interval::Interval, vec3::Vec3};
+use crate::sampling::sampler::Sampler;
+use crate::spectral::{sampled_spectrum::SampledSpectrum, sampled_wavelengths::SampledWavelengths};
+
+#[derive(Default, Clone, Copy)]
+pub enum RenderMode {
+    #[default]
+    Rgb,
+    Spectral,
+}
+
+pub struct PathIntegrator {
+    max_bounces: u32,
+    roulette_depth: Option<u32>,
+    mode: RenderMode,
+}
+
+impl PathIntegrator {
+    pub fn new(max_bounces: u32) -> Self {
+        const ROULETTE_DEPTH: u32 = 3;
+
+        Self {
+            max_bounces,
+            roulette_depth: Some(ROULETTE_DEPTH),
+            mode: RenderMode::default(),
+        }
+    }
+
+    pub fn with_render_mode(mut self, mode: RenderMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    // Paths are subject to Russian roulette once they have bounced `depth` times;
+    // `None` disables it so that paths only end at the bounce limit.
+    pub fn with_russian_roulette(mut self, depth: Option<u32>) -> Self {
+        self.roulette_depth = depth;
+        self
+    }
+
+    // A path that escapes the scene picks up the background, while one that is
+    // still bouncing when the depth budget runs out contributes nothing further.
+    fn radiance_rgb(&self, ray: &Ray, scene: &Scene, sampler: &mut dyn Sampler) -> Pixel {
+        let mut radiance = Pixel::default();
+        let mut throughput = Pixel::from(&Vec3::new(1.0, 1.0, 1.0));
+        let mut ray = ray.clone();
+
+        for bounces in 0.. {
+            let record = match scene.hit(&ray, &Interval::new(0.001, f32::INFINITY)) {
+                None => {
+                    radiance += throughput * Pixel::from_miss(ray.direction(), scene.color_space);
+                    break;
+                }
+                Some(_) if bounces == self.max_bounces => break,
+                Some(record) => record,
+            };
+
+            radiance += throughput.clone() * PathIntegrator::direct_light(&ray, &record, scene);
+
+            let Some(res) = Material::scatter(&ray, &record, sampler) else {
+                break;
+            };
+            throughput = throughput * res.attenuation;
+
+            match self.survival_probability(bounces, throughput.max_component(), sampler) {
+                Some(p) => throughput = 1.0 / p * throughput,
+                None => break,
+            }
+            ray = res.ray;
+        }
+
+        radiance
+    }
+
+    fn radiance_spectral(&self, ray: &Ray, scene: &Scene, sampler: &mut dyn Sampler) -> Pixel {
+        let mut wavelengths = SampledWavelengths::random(sampler);
+        let mut radiance = SampledSpectrum::default();
+        let mut throughput = SampledSpectrum::constant(1.0);
+        let mut ray = ray.clone();
+
+        for bounces in 0.. {
+            let record = match scene.hit(&ray, &Interval::new(0.001, f32::INFINITY)) {
+                None => {
+                    let background = Pixel::from_miss(ray.direction(), scene.color_space);
+                    radiance = radiance
+                        + throughput
+                            * SampledSpectrum::from_rgb_illuminant(
+                                &background,
+                                scene.color_space,
+                                &wavelengths,
+                            );
+                    break;
+                }
+                Some(_) if bounces == self.max_bounces => break,
+                Some(record) => record,
+            };
+
+            let direct = PathIntegrator::direct_light_spectral(&ray, &record, scene, &wavelengths);
+            radiance = radiance + throughput.clone() * direct;
+
+            let Some(res) = Material::scatter_spectral(
+                &ray,
+                &record,
+                &mut wavelengths,
+                scene.color_space,
+                sampler,
+            ) else {
+                break;
+            };
+            throughput = throughput * res.attenuation;
+
+            match self.survival_probability(bounces, throughput.max_value(), sampler) {
+                Some(p) => throughput = 1.0 / p * throughput,
+                None => break,
+            }
+            ray = res.ray;
+        }
+
+        radiance.to_pixel(&wavelengths, scene.color_space)
+    }
+
+    // Returns the probability with which the path is continued, or `None` if
+    // Russian roulette terminates it.
+    fn survival_probability(
+        &self,
+        bounces: u32,
+        max_throughput: f32,
+        sampler: &mut dyn Sampler,
+    ) -> Option<f32> {
+        match self.roulette_depth {
+            Some(roulette_depth) if bounces >= roulette_depth => {
+                let p = max_throughput.clamp(0.0, 1.0);
+                match p > sampler.get_1d() {
+                    true => Some(p),
+                    false => None,
+                }
+            }
+            _ => Some(1.0),
+        }
+    }
+
+    pub fn direct_light(ray: &Ray, record: &HitRecord, scene: &Scene) -> Pixel {
+        scene
+            .lights
+            .iter()
+            .filter_map(|light| light.sample_li(&record.point))
+            .filter_map(|sample| {
+                let f = Material::eval(ray, record, &sample.direction);
+                let shadow_ray = Ray::new(record.point.clone(), sample.direction.clone());
+                match scene.occluded(&shadow_ray, sample.distance) {
+                    true => None,
+                    false => Some(f * sample.radiance),
+                }
+            })
+            .fold(Pixel::default(), |acc, contribution| acc + contribution)
+    }
+
+    pub fn direct_light_spectral(
+        ray: &Ray,
+        record: &HitRecord,
+        scene: &Scene,
+        wavelengths: &SampledWavelengths,
+    ) -> SampledSpectrum {
+        scene
+            .lights
+            .iter()
+            .filter_map(|light| light.sample_li(&record.point))
+            .filter_map(|sample| {
+                let f = Material::eval(ray, record, &sample.direction);
+                let shadow_ray = Ray::new(record.point.clone(), sample.direction.clone());
+                match scene.occluded(&shadow_ray, sample.distance) {
+                    true => None,
+                    false => Some(
+                        SampledSpectrum::from_rgb(&f, scene.color_space, wavelengths)
+                            * SampledSpectrum::from_rgb_illuminant(
+                                &sample.radiance,
+                                scene.color_space,
+                                wavelengths,
+                            ),
+                    ),
+                }
+            })
+            .fold(SampledSpectrum::default(), |acc, contribution| {
+                acc + contribution
+            })
+    }
+}
+
+impl Integrator for PathIntegrator {
+    fn name(&self) -> &'static str {
+        "path tracing"
+    }
+
+    fn radiance(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        _camera: &Camera,
+        sampler: &mut dyn Sampler,
+        _splats: &mut Vec<Splat>,
+    ) -> Pixel {
+        match self.mode {
+            RenderMode::Rgb => self.radiance_rgb(ray, scene, sampler),
+            RenderMode::Spectral => self.radiance_spectral(ray, scene, sampler),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::graphics::pixel::Pixel;
+use crate::math::vec3::Vec3;
+
+use std::collections::HashMap;
+
+pub struct Photon {
+    pub point: Vec3,
+    pub normal: Vec3,
+    // Direction of travel when the photon landed.
+    pub direction: Vec3,
+    pub power: Pixel,
+}
+
+impl Photon {
+    pub fn new(point: Vec3, normal: Vec3, direction: Vec3, power: Pixel) -> Self {
+        Self {
+            point,
+            normal,
+            direction,
+            power,
+        }
+    }
+}
+
+// Uniform hash grid with cells twice the lookup radius, so that a lookup
+// touches at most two cells along each axis.
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    cells: HashMap<(i32, i32, i32), Vec<usize>>,
+    cell_size: f32,
+    radius: f32,
+}
+
+impl PhotonMap {
+    pub fn new(photons: Vec<Photon>, radius: f32) -> Self {
+        let cell_size = 2.0 * radius;
+        let mut cells: HashMap<(i32, i32, i32), Vec<usize>> = HashMap::new();
+        photons.iter().enumerate().for_each(|(index, photon)| {
+            let cell = PhotonMap::cell(&photon.point, cell_size);
+            cells.entry(cell).or_default().push(index);
+        });
+
+        Self {
+            photons,
+            cells,
+            cell_size,
+            radius,
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    pub fn near<'a>(&'a self, point: &'a Vec3) -> impl Iterator<Item = &'a Photon> + 'a {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        let lo = PhotonMap::cell(&(point - &r), self.cell_size);
+        let hi = PhotonMap::cell(&(point + &r), self.cell_size);
+        let radius_squared = self.radius * self.radius;
+
+        (lo.0..=hi.0)
+            .flat_map(move |x| {
+                (lo.1..=hi.1).flat_map(move |y| (lo.2..=hi.2).map(move |z| (x, y, z)))
+            })
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .map(|&index| &self.photons[index])
+            .filter(move |photon| (&photon.point - point).norm_squared() <= radius_squared)
+    }
+
+    fn cell(point: &Vec3, cell_size: f32) -> (i32, i32, i32) {
+        (
+            (point.x / cell_size).floor() as i32,
+            (point.y / cell_size).floor() as i32,
+            (point.z / cell_size).floor() as i32,
+        )
+    }
+}
@@ -0,0 +1,289 @@
+use crate::geometry::{hit_record::HitRecord, hittable::Hittable, ray::Ray};
+use crate::graphics::{camera::Camera, film::Splat, pixel::Pixel, scene::Scene};
+use crate::integrators::{
+    integrator::{self, Integrator},
+    path_integrator::PathIntegrator,
+    photon_map::{Photon, PhotonMap},
+};
+use crate::materials::{material::Material, scatter::Scatter};
+use crate::math::{interval::Interval, vec3::Vec3};
+use crate::sampling::sampler::Sampler;
+
+// Path tracing with caustics from a photon map. Point, spot and directional
+// lights cannot be reached through specular surfaces by camera paths, so
+// photons are only stored on their first diffuse hit after one or more
+// specular bounces and everything else is left to the path tracer. Lights at
+// infinity shoot photons at the bounds of the specular surfaces, as no other
+// photon can end up in the map.
+//
+// Every pass shoots a new photon map with a radius that shrinks as in the
+// probabilistic formulation of progressive photon mapping (Knaus and Zwicker
+// 2011), so averaging the passes converges to the correct caustics.
+pub struct PhotonMappingIntegrator {
+    max_bounces: u32,
+    photons_per_pass: u32,
+    initial_radius: f32,
+    alpha: f32,
+    photon_map: Option<PhotonMap>,
+}
+
+impl PhotonMappingIntegrator {
+    pub fn new(max_bounces: u32) -> Self {
+        const PHOTONS_PER_PASS: u32 = 100_000;
+        const INITIAL_RADIUS: f32 = 0.1;
+        const ALPHA: f32 = 2.0 / 3.0;
+
+        Self {
+            max_bounces,
+            photons_per_pass: PHOTONS_PER_PASS,
+            initial_radius: INITIAL_RADIUS,
+            alpha: ALPHA,
+            photon_map: None,
+        }
+    }
+
+    pub fn with_photons_per_pass(mut self, photons: u32) -> Self {
+        self.photons_per_pass = photons;
+        self
+    }
+
+    pub fn with_initial_radius(mut self, radius: f32) -> Self {
+        self.initial_radius = radius;
+        self
+    }
+
+    // Fraction of photons kept from one pass to the next, in (0, 1). Smaller
+    // values shrink the radius faster, trading noise for bias.
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    fn radius(&self, pass: u32) -> f32 {
+        let radius_squared = (1..=pass).fold(self.initial_radius * self.initial_radius, |r2, i| {
+            r2 * (i as f32 - 1.0 + self.alpha) / i as f32
+        });
+        radius_squared.sqrt()
+    }
+
+    fn shoot_photons(&self, scene: &Scene, sampler: &mut dyn Sampler) -> Vec<Photon> {
+        let bounds = scene.specular_bounds();
+        let lights: Vec<_> = scene
+            .lights
+            .iter()
+            .filter(|light| !light.is_infinite() || bounds.is_some())
+            .collect();
+        if lights.is_empty() {
+            return vec![];
+        }
+
+        let pmf = 1.0 / lights.len() as f32;
+        let mut photons = vec![];
+
+        (0..self.photons_per_pass).for_each(|_| {
+            let index = ((sampler.get_1d() * lights.len() as f32) as usize).min(lights.len() - 1);
+            let light = lights[index];
+            let emission = match (light.is_infinite(), &bounds) {
+                (true, Some(bounds)) => light.sample_le_towards(bounds, sampler),
+                _ => light.sample_le(sampler),
+            };
+            let Some(emission) = emission else {
+                return;
+            };
+            // Photons from infinity start at the bounds, so anything between
+            // them and the light has to be checked separately.
+            if light.is_infinite() {
+                let towards_light =
+                    Ray::new(emission.ray.origin().clone(), -emission.ray.direction());
+                if scene.occluded(&towards_light, f32::INFINITY) {
+                    return;
+                }
+            }
+            if emission.pdf_direction <= 0.0 {
+                return;
+            }
+
+            let scale = pmf * emission.pdf_direction * self.photons_per_pass as f32;
+            let mut power = 1.0 / scale * emission.intensity;
+            let mut ray = emission.ray;
+            let mut specular = false;
+
+            for _ in 0..self.max_bounces {
+                let Some(record) = scene.hit(&ray, &Interval::new(0.001, f32::INFINITY)) else {
+                    break;
+                };
+
+                if !record.material.is_specular() {
+                    if specular {
+                        let direction = ray.direction().unit_vector();
+                        photons.push(Photon::new(record.point, record.normal, direction, power));
+                    }
+                    break;
+                }
+
+                let Some(res) = Material::scatter(&ray, &record, sampler) else {
+                    break;
+                };
+                power = power * res.attenuation;
+                specular = true;
+                ray = res.ray;
+            }
+        });
+
+        photons
+    }
+
+    fn caustics(&self, ray: &Ray, record: &HitRecord) -> Pixel {
+        let Some(photon_map) = &self.photon_map else {
+            return Pixel::default();
+        };
+        if photon_map.is_empty() || record.material.is_specular() {
+            return Pixel::default();
+        }
+
+        let flux = photon_map
+            .near(&record.point)
+            .filter(|photon| photon.normal.dot(&record.normal) > 0.0)
+            .filter_map(|photon| {
+                let wi = -&photon.direction;
+                let cos_theta = record.shading_normal.dot(&wi);
+                match cos_theta > 1e-4 {
+                    true => Some(
+                        1.0 / cos_theta * Material::eval(ray, record, &wi) * photon.power.clone(),
+                    ),
+                    false => None,
+                }
+            })
+            .fold(Pixel::default(), |acc, contribution| acc + contribution);
+
+        let radius = photon_map.radius();
+        1.0 / (std::f32::consts::PI * radius * radius) * flux
+    }
+}
+
+impl Integrator for PhotonMappingIntegrator {
+    fn name(&self) -> &'static str {
+        "photon mapping"
+    }
+
+    fn validate(&self, scene: &Scene) -> Result<(), String> {
+        integrator::validate_inverse_square_falloff(scene)
+    }
+
+    fn begin_pass(
+        &mut self,
+        scene: &Scene,
+        _camera: &Camera,
+        pass: u32,
+        sampler: &mut dyn Sampler,
+    ) {
+        let photons = self.shoot_photons(scene, sampler);
+        self.photon_map = Some(PhotonMap::new(photons, self.radius(pass)));
+    }
+
+    fn radiance(
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        _camera: &Camera,
+        sampler: &mut dyn Sampler,
+        _splats: &mut Vec<Splat>,
+    ) -> Pixel {
+        let mut radiance = Pixel::default();
+        let mut throughput = Pixel::from(&Vec3::new(1.0, 1.0, 1.0));
+        let mut ray = ray.clone();
+
+        for bounces in 0.. {
+            let record = match scene.hit(&ray, &Interval::new(0.001, f32::INFINITY)) {
+                None => {
+                    radiance += throughput * Pixel::from_miss(ray.direction(), scene.color_space);
+                    break;
+                }
+                Some(_) if bounces == self.max_bounces => break,
+                Some(record) => record,
+            };
+
+            let direct =
+                PathIntegrator::direct_light(&ray, &record, scene) + self.caustics(&ray, &record);
+            radiance += throughput.clone() * direct;
+
+            let Some(res) = Material::scatter(&ray, &record, sampler) else {
+                break;
+            };
+            throughput = throughput * res.attenuation;
+            ray = res.ray;
+        }
+
+        radiance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{hittable_list::HittableList, quad::Quad, sphere::Sphere};
+    use crate::lights::{directional_light::DirectionalLight, light_list::LightList};
+    use crate::materials::refractive_index::RefractiveIndex;
+    use crate::sampling::independent_sampler::IndependentSampler;
+
+    fn glass_sphere_scene() -> Scene {
+        let mut objects = HittableList::new();
+        objects.push(Box::new(Quad::new(
+            Vec3::new(-5.0, 0.0, -5.0),
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::new(10.0, 0.0, 0.0),
+            Material::Lambertian(Pixel::from(&Vec3::new(0.5, 0.5, 0.5))),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, 1.5, 0.0),
+            1.0,
+            Material::Dielectric(RefractiveIndex::Constant(1.5), None),
+        )));
+        let mut lights = LightList::new();
+        lights.push(Box::new(DirectionalLight::new(
+            Vec3::new(0.0, -1.0, 0.0),
+            Pixel::from(&Vec3::new(1.0, 1.0, 1.0)),
+        )));
+        Scene::new(objects, lights)
+    }
+
+    #[test]
+    fn directional_lights_cast_caustics() {
+        let scene = glass_sphere_scene();
+        let mut integrator = PhotonMappingIntegrator::new(8)
+            .with_photons_per_pass(10_000)
+            .with_initial_radius(0.2);
+        let mut sampler = IndependentSampler::new(1);
+        integrator.begin_pass(&scene, &Camera::default(), 1, &mut sampler);
+
+        // Grazes under the sphere to the ground right below its center.
+        let ray = Ray::new(Vec3::new(3.0, 0.3, 0.0), Vec3::new(-3.0, -0.3, 0.0));
+        let record = scene
+            .hit(&ray, &Interval::new(0.001, f32::INFINITY))
+            .unwrap();
+        assert!(record.point.x.abs() < 1e-3);
+        // The sphere focuses close to the ground, so the caustic outshines
+        // the radiance of the ground under direct light.
+        let direct = 0.5 * std::f32::consts::FRAC_1_PI;
+        assert!(integrator.caustics(&ray, &record).luminance() > direct);
+    }
+
+    #[test]
+    fn occluded_directional_lights_cast_no_caustics() {
+        let mut scene = glass_sphere_scene();
+        let mut objects = HittableList::new();
+        objects.push(Box::new(Quad::new(
+            Vec3::new(-5.0, 4.0, -5.0),
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::new(10.0, 0.0, 0.0),
+            Material::Lambertian(Pixel::from(&Vec3::new(0.5, 0.5, 0.5))),
+        )));
+        objects.push(Box::new(scene.objects));
+        scene.objects = objects;
+
+        let mut integrator = PhotonMappingIntegrator::new(8).with_photons_per_pass(1_000);
+        let mut sampler = IndependentSampler::new(1);
+        integrator.begin_pass(&scene, &Camera::default(), 1, &mut sampler);
+        assert!(integrator.photon_map.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,6 @@
+pub mod directional_light;
+pub mod ies_profile;
+pub mod light;
+pub mod light_list;
+pub mod point_light;
+pub mod spot_light;
@@ -0,0 +1,57 @@
+use crate::geometry::{bounding_sphere::BoundingSphere, ray::Ray};
+use crate::graphics::pixel::Pixel;
+use crate::lights::light::{Light, LightEmission, LightSample};
+use crate::math::vec3::Vec3;
+use crate::sampling::sampler::Sampler;
+
+pub struct DirectionalLight {
+    pub direction: Vec3,
+    pub irradiance: Pixel,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, irradiance: Pixel) -> Self {
+        Self {
+            direction: direction.unit_vector(),
+            irradiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample_li(&self, _point: &Vec3) -> Option<LightSample> {
+        Some(LightSample::new(
+            -&self.direction,
+            f32::INFINITY,
+            self.irradiance.clone(),
+        ))
+    }
+
+    fn sample_le_towards(
+        &self,
+        bounds: &BoundingSphere,
+        sampler: &mut dyn Sampler,
+    ) -> Option<LightEmission> {
+        let axis = match self.direction.x.abs() > 0.9 {
+            true => Vec3::new(0.0, 0.0, 1.0),
+            false => Vec3::new(1.0, 0.0, 0.0),
+        };
+        let u = self.direction.cross(&axis).unit_vector();
+        let v = self.direction.cross(&u);
+        let disk = Vec3::random_in_unit_disk(sampler);
+
+        let radius = bounds.radius;
+        let origin =
+            &bounds.center - radius * &self.direction + radius * disk.x * &u + radius * disk.y * &v;
+        let area = std::f32::consts::PI * radius * radius;
+        Some(LightEmission::new(
+            Ray::new(origin, self.direction.clone()),
+            area * self.irradiance.clone(),
+            1.0,
+        ))
+    }
+
+    fn is_infinite(&self) -> bool {
+        true
+    }
+}
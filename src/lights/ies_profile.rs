@@ -0,0 +1,365 @@
+use crate::math::vec3::Vec3;
+
+use std::fmt;
+use std::fs;
+
+#[derive(Debug)]
+pub enum IesError {
+    Io(std::io::Error),
+    MissingTilt,
+    UnsupportedTilt(String),
+    UnexpectedEnd,
+    InvalidNumber(String),
+    InvalidHeader(String),
+    InvalidAngles(String),
+}
+
+impl fmt::Display for IesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IesError::Io(err) => write!(f, "failed to read IES file: {}", err),
+            IesError::MissingTilt => write!(f, "missing TILT line"),
+            IesError::UnsupportedTilt(tilt) => {
+                write!(f, "unsupported TILT specification: {}", tilt)
+            }
+            IesError::UnexpectedEnd => write!(f, "unexpected end of photometric data"),
+            IesError::InvalidNumber(token) => write!(f, "invalid number: {}", token),
+            IesError::InvalidHeader(msg) => write!(f, "invalid photometric header: {}", msg),
+            IesError::InvalidAngles(msg) => write!(f, "invalid angles: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for IesError {}
+
+impl From<std::io::Error> for IesError {
+    fn from(err: std::io::Error) -> Self {
+        IesError::Io(err)
+    }
+}
+
+// Type C photometric profile as described by IESNA LM-63. Vertical angles are
+// measured from the nadir, horizontal angles around it.
+pub struct IesProfile {
+    vertical_angles: Vec<f32>,
+    horizontal_angles: Vec<f32>,
+    candela: Vec<Vec<f32>>,
+    max_candela: f32,
+}
+
+impl IesProfile {
+    pub fn from_file(path: &str) -> Result<Self, IesError> {
+        IesProfile::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, IesError> {
+        let mut lines = content.lines();
+        let tilt = lines
+            .by_ref()
+            .map(str::trim)
+            .find(|line| line.starts_with("TILT"))
+            .ok_or(IesError::MissingTilt)?;
+
+        let rest = lines.collect::<Vec<&str>>().join(" ");
+        let mut tokens = rest
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty());
+        let mut next = || -> Result<f32, IesError> {
+            let token = tokens.next().ok_or(IesError::UnexpectedEnd)?;
+            token
+                .parse::<f32>()
+                .map_err(|_| IesError::InvalidNumber(token.to_string()))
+        };
+
+        match tilt
+            .trim_start_matches("TILT")
+            .trim_start_matches('=')
+            .trim()
+        {
+            "NONE" => {}
+            "INCLUDE" => {
+                let _lamp_geometry = next()?;
+                let n_tilt = IesProfile::count(next()?, "tilt angle count")?;
+                (0..2 * n_tilt).try_for_each(|_| next().map(|_| ()))?;
+            }
+            other => return Err(IesError::UnsupportedTilt(other.to_string())),
+        }
+
+        let _n_lamps = next()?;
+        let _lumens_per_lamp = next()?;
+        let multiplier = next()?;
+        let n_vertical = IesProfile::count(next()?, "vertical angle count")?;
+        let n_horizontal = IesProfile::count(next()?, "horizontal angle count")?;
+        let photometric_type = next()?;
+        let _units = next()?;
+        let _dimensions = (next()?, next()?, next()?);
+        let ballast_factor = next()?;
+        let _future_use = next()?;
+        let _input_watts = next()?;
+
+        if photometric_type != 1.0 {
+            return Err(IesError::InvalidHeader(format!(
+                "only type C photometry is supported, got type {}",
+                photometric_type
+            )));
+        }
+
+        let vertical_angles = (0..n_vertical)
+            .map(|_| next())
+            .collect::<Result<Vec<f32>, IesError>>()?;
+        let horizontal_angles = (0..n_horizontal)
+            .map(|_| next())
+            .collect::<Result<Vec<f32>, IesError>>()?;
+        let candela = (0..n_horizontal)
+            .map(|_| {
+                (0..n_vertical)
+                    .map(|_| next().map(|val| multiplier * ballast_factor * val))
+                    .collect::<Result<Vec<f32>, IesError>>()
+            })
+            .collect::<Result<Vec<Vec<f32>>, IesError>>()?;
+
+        IesProfile::validate_angles(&vertical_angles, 0.0, 180.0, "vertical")?;
+        IesProfile::validate_angles(&horizontal_angles, 0.0, 360.0, "horizontal")?;
+
+        let max_candela = candela.iter().flatten().copied().fold(0.0, f32::max);
+        if max_candela <= 0.0 {
+            return Err(IesError::InvalidHeader(
+                "profile does not emit any light".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+            max_candela,
+        })
+    }
+
+    pub fn max_candela(&self) -> f32 {
+        self.max_candela
+    }
+
+    // Intensity relative to the brightest direction of the profile, for a unit
+    // direction given in a frame with the nadir along `down` and horizontal
+    // angle zero along `reference`. Only the shape of the distribution is
+    // kept, lights scale it by their own intensity; to reproduce the absolute
+    // output of the file, give the light an intensity of `max_candela`.
+    pub fn relative_intensity(&self, direction: &Vec3, down: &Vec3, reference: &Vec3) -> f32 {
+        let vertical = direction.dot(down).clamp(-1.0, 1.0).acos().to_degrees();
+        let x = direction.dot(reference);
+        let y = direction.dot(&down.cross(reference));
+        let horizontal = y.atan2(x).to_degrees().rem_euclid(360.0);
+        self.intensity(vertical, horizontal) / self.max_candela
+    }
+
+    pub fn intensity(&self, vertical: f32, horizontal: f32) -> f32 {
+        let horizontal = self.fold_horizontal(horizontal);
+        match IesProfile::bracket(&self.horizontal_angles, horizontal) {
+            Some((i, t)) if t > 0.0 => {
+                (1.0 - t) * self.vertical_intensity(i, vertical)
+                    + t * self.vertical_intensity(i + 1, vertical)
+            }
+            Some((i, _)) => self.vertical_intensity(i, vertical),
+            None => self.wrap_horizontal(vertical, horizontal),
+        }
+    }
+
+    // Interpolates across the gap between the last horizontal angle and the
+    // first one plus 360 degrees, for files that do not end on a symmetry plane.
+    fn wrap_horizontal(&self, vertical: f32, horizontal: f32) -> f32 {
+        let last_index = self.horizontal_angles.len() - 1;
+        let first = self.horizontal_angles[0];
+        let last = self.horizontal_angles[last_index];
+        let horizontal = match horizontal < first {
+            true => horizontal + 360.0,
+            false => horizontal,
+        };
+        let t = (horizontal - last) / (first + 360.0 - last);
+        (1.0 - t) * self.vertical_intensity(last_index, vertical)
+            + t * self.vertical_intensity(0, vertical)
+    }
+
+    fn vertical_intensity(&self, i: usize, vertical: f32) -> f32 {
+        let row = &self.candela[i];
+        match IesProfile::bracket(&self.vertical_angles, vertical) {
+            Some((j, t)) if t > 0.0 => (1.0 - t) * row[j] + t * row[j + 1],
+            Some((j, _)) => row[j],
+            None => 0.0,
+        }
+    }
+
+    // Maps a horizontal angle into the range covered by the file according to
+    // the symmetry implied by its first and last horizontal angles.
+    fn fold_horizontal(&self, horizontal: f32) -> f32 {
+        let first = self.horizontal_angles[0];
+        let last = *self.horizontal_angles.last().unwrap();
+        if self.horizontal_angles.len() == 1 || last == 0.0 {
+            first
+        } else if first == 90.0 && last == 270.0 {
+            // Symmetric about the 90-270 degree plane.
+            match horizontal {
+                h if h < 90.0 => 180.0 - h,
+                h if h > 270.0 => 540.0 - h,
+                h => h,
+            }
+        } else if last == 90.0 {
+            let h = horizontal % 180.0;
+            match h > 90.0 {
+                true => 180.0 - h,
+                false => h,
+            }
+        } else if last == 180.0 {
+            match horizontal > 180.0 {
+                true => 360.0 - horizontal,
+                false => horizontal,
+            }
+        } else {
+            horizontal
+        }
+    }
+
+    fn bracket(angles: &[f32], angle: f32) -> Option<(usize, f32)> {
+        let (first, last) = (angles[0], *angles.last().unwrap());
+        if angle < first || angle > last {
+            return None;
+        }
+        if angles.len() == 1 {
+            return Some((0, 0.0));
+        }
+        let i = angles
+            .windows(2)
+            .position(|w| angle <= w[1])
+            .unwrap_or(angles.len() - 2);
+        let span = angles[i + 1] - angles[i];
+        Some((i, (angle - angles[i]) / span))
+    }
+
+    fn count(val: f32, what: &str) -> Result<usize, IesError> {
+        match val >= 1.0 && val.fract() == 0.0 {
+            true => Ok(val as usize),
+            false => Err(IesError::InvalidHeader(format!(
+                "{} must be a positive integer",
+                what
+            ))),
+        }
+    }
+
+    fn validate_angles(angles: &[f32], lb: f32, ub: f32, what: &str) -> Result<(), IesError> {
+        if angles.iter().any(|&a| a < lb || a > ub) {
+            return Err(IesError::InvalidAngles(format!(
+                "{} angles must lie within [{}, {}]",
+                what, lb, ub
+            )));
+        }
+        if angles.windows(2).any(|w| w[1] <= w[0]) {
+            return Err(IesError::InvalidAngles(format!(
+                "{} angles must be strictly increasing",
+                what
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "IESNA:LM-63-2002\n[TEST] unit test\nTILT=NONE\n";
+
+    fn profile(body: &str) -> Result<IesProfile, IesError> {
+        IesProfile::parse(&format!("{}{}", HEADER, body))
+    }
+
+    #[test]
+    fn parses_rotationally_symmetric_profile() {
+        let profile = profile("1 1000 1 3 1 1 1 0 0 0\n1 1 100\n0 45 90\n0\n100 50 0\n").unwrap();
+        assert_eq!(profile.max_candela(), 100.0);
+        assert_eq!(profile.intensity(0.0, 123.0), 100.0);
+        assert_eq!(profile.intensity(22.5, 0.0), 75.0);
+        assert_eq!(profile.intensity(120.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn applies_multiplier_and_ballast_factor() {
+        let profile = profile("1 1000 2 1 1 1 1 0 0 0\n0.5 1 100\n0\n0\n10\n").unwrap();
+        assert_eq!(profile.intensity(0.0, 0.0), 10.0);
+    }
+
+    #[test]
+    fn skips_included_tilt_data() {
+        let content = "TILT=INCLUDE\n1\n2\n0 90\n1 1\n1 1000 1 1 1 1 1 0 0 0\n1 1 100\n0\n0\n10\n";
+        let profile = IesProfile::parse(content).unwrap();
+        assert_eq!(profile.intensity(0.0, 0.0), 10.0);
+    }
+
+    #[test]
+    fn folds_bilateral_profiles() {
+        let profile =
+            profile("1 1000 1 1 3 1 1 0 0 0\n1 1 100\n0\n0 90 180\n10\n20\n30\n").unwrap();
+        assert_eq!(profile.intensity(0.0, 270.0), 20.0);
+        assert_eq!(profile.intensity(0.0, 315.0), 15.0);
+    }
+
+    #[test]
+    fn folds_profiles_starting_at_90_degrees() {
+        let profile =
+            profile("1 1000 1 1 3 1 1 0 0 0\n1 1 100\n0\n90 180 270\n10\n20\n30\n").unwrap();
+        assert_eq!(profile.intensity(0.0, 90.0), 10.0);
+        assert_eq!(profile.intensity(0.0, 0.0), 20.0);
+        assert_eq!(profile.intensity(0.0, 45.0), 15.0);
+        assert_eq!(profile.intensity(0.0, 315.0), 25.0);
+    }
+
+    #[test]
+    fn wraps_profiles_not_ending_on_a_symmetry_plane() {
+        let profile =
+            profile("1 1000 1 1 4 1 1 0 0 0\n1 1 100\n0\n0 120 240 350\n10\n20\n30\n40\n").unwrap();
+        assert_eq!(profile.intensity(0.0, 350.0), 40.0);
+        assert_eq!(profile.intensity(0.0, 355.0), 25.0);
+        assert_eq!(profile.intensity(0.0, 0.0), 10.0);
+    }
+
+    #[test]
+    fn rejects_missing_tilt() {
+        let result = IesProfile::parse("IESNA:LM-63-2002\n1 1000 1 1 1 1 1 0 0 0\n");
+        assert!(matches!(result, Err(IesError::MissingTilt)));
+    }
+
+    #[test]
+    fn rejects_tilt_files() {
+        let result = IesProfile::parse("TILT=lamp.tlt\n");
+        assert!(matches!(result, Err(IesError::UnsupportedTilt(_))));
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let result = profile("1 1000 1 3 1 1 1 0 0 0\n1 1 100\n0 45 90\n0\n100 50\n");
+        assert!(matches!(result, Err(IesError::UnexpectedEnd)));
+    }
+
+    #[test]
+    fn rejects_malformed_numbers() {
+        let result = profile("1 1000 1 1 1 1 1 0 0 0\n1 1 100\n0\n0\nbright\n");
+        assert!(matches!(result, Err(IesError::InvalidNumber(token)) if token == "bright"));
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let result = profile("1 1000 1 0 1 1 1 0 0 0\n1 1 100\n");
+        assert!(matches!(result, Err(IesError::InvalidHeader(_))));
+        let result = profile("1 1000 1 1 1 2 1 0 0 0\n1 1 100\n0\n0\n10\n");
+        assert!(matches!(result, Err(IesError::InvalidHeader(_))));
+        let result = profile("1 1000 1 1 1 1 1 0 0 0\n1 1 100\n0\n0\n0\n");
+        assert!(matches!(result, Err(IesError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_angles() {
+        let result = profile("1 1000 1 2 1 1 1 0 0 0\n1 1 100\n45 0\n0\n10 10\n");
+        assert!(matches!(result, Err(IesError::InvalidAngles(_))));
+        let result = profile("1 1000 1 1 1 1 1 0 0 0\n1 1 100\n0\n400\n10\n");
+        assert!(matches!(result, Err(IesError::InvalidAngles(_))));
+    }
+}
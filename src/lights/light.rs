@@ -0,0 +1,75 @@
+use crate::geometry::{bounding_sphere::BoundingSphere, ray::Ray};
+use crate::graphics::pixel::Pixel;
+use crate::math::vec3::Vec3;
+use crate::sampling::sampler::Sampler;
+
+pub struct LightSample {
+    pub direction: Vec3,
+    pub distance: f32,
+    pub radiance: Pixel,
+}
+
+impl LightSample {
+    pub fn new(direction: Vec3, distance: f32, radiance: Pixel) -> Self {
+        Self {
+            direction,
+            distance,
+            radiance,
+        }
+    }
+}
+
+pub struct LightEmission {
+    pub ray: Ray,
+    pub intensity: Pixel,
+    pub pdf_direction: f32,
+}
+
+impl LightEmission {
+    pub fn new(ray: Ray, intensity: Pixel, pdf_direction: f32) -> Self {
+        Self {
+            ray,
+            intensity,
+            pdf_direction,
+        }
+    }
+}
+
+pub trait Light: Send + Sync {
+    fn sample_li(&self, point: &Vec3) -> Option<LightSample>;
+
+    // Samples a ray leaving the light for tracing light subpaths. Emission
+    // assumes an inverse square falloff, see `falloff`.
+    fn sample_le(&self, _sampler: &mut dyn Sampler) -> Option<LightEmission> {
+        None
+    }
+
+    // Samples a ray from a light at infinity that crosses `bounds`, starting
+    // on the disk facing the light. Its intensity is the power through the
+    // disk and its density is one, so that photons are weighted as for
+    // finite lights.
+    fn sample_le_towards(
+        &self,
+        _bounds: &BoundingSphere,
+        _sampler: &mut dyn Sampler,
+    ) -> Option<LightEmission> {
+        None
+    }
+
+    // Solid angle density of sample_le emitting along `direction`.
+    fn pdf_le(&self, _direction: &Vec3) -> f32 {
+        0.0
+    }
+
+    // Exponent with which intensity falls off over distance. Light subpaths
+    // carry intensity unchanged along their rays, so only the physical
+    // inverse square law can be traced from the light.
+    fn falloff(&self) -> f32 {
+        2.0
+    }
+
+    // Lights at infinity can only be reached through shadow rays.
+    fn is_infinite(&self) -> bool {
+        false
+    }
+}
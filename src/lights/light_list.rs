@@ -0,0 +1,37 @@
+use crate::lights::light::Light;
+
+pub struct LightList {
+    lights: Vec<Box<dyn Light>>,
+}
+
+impl Default for LightList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LightList {
+    pub fn new() -> Self {
+        Self { lights: vec![] }
+    }
+
+    pub fn push(&mut self, light: Box<dyn Light>) {
+        self.lights.push(light);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&dyn Light> {
+        self.lights.get(index).map(|light| light.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Box<dyn Light>> {
+        self.lights.iter()
+    }
+}
@@ -0,0 +1,88 @@
+use crate::geometry::ray::Ray;
+use crate::graphics::pixel::Pixel;
+use crate::lights::{
+    ies_profile::IesProfile,
+    light::{Light, LightEmission, LightSample},
+};
+use crate::math::vec3::Vec3;
+use crate::sampling::sampler::Sampler;
+
+use std::sync::Arc;
+
+pub struct PointLight {
+    pub position: Vec3,
+    pub intensity: Pixel,
+    pub falloff: f32,
+    pub profile: Option<Arc<IesProfile>>,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, intensity: Pixel) -> Self {
+        Self {
+            position,
+            intensity,
+            falloff: 2.0,
+            profile: None,
+        }
+    }
+
+    // The profile's nadir points along -y and its zero horizontal angle along +x.
+    pub fn with_profile(mut self, profile: Arc<IesProfile>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub fn with_falloff(mut self, falloff: f32) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    fn distribution(&self, direction: &Vec3) -> f32 {
+        match &self.profile {
+            Some(profile) => profile.relative_intensity(
+                direction,
+                &Vec3::new(0.0, -1.0, 0.0),
+                &Vec3::new(1.0, 0.0, 0.0),
+            ),
+            None => 1.0,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample_li(&self, point: &Vec3) -> Option<LightSample> {
+        let to_light = &self.position - point;
+        let distance = to_light.norm();
+        if distance <= 0.0 {
+            return None;
+        }
+
+        let direction = to_light / distance;
+        let distribution = self.distribution(&-&direction);
+        if distribution <= 0.0 {
+            return None;
+        }
+
+        let radiance = distribution * distance.powf(-self.falloff) * self.intensity.clone();
+        Some(LightSample::new(direction, distance, radiance))
+    }
+
+    fn sample_le(&self, sampler: &mut dyn Sampler) -> Option<LightEmission> {
+        let direction = Vec3::random_unit_vector(sampler);
+        let intensity = self.distribution(&direction) * self.intensity.clone();
+        let ray = Ray::new(self.position.clone(), direction);
+        Some(LightEmission::new(
+            ray,
+            intensity,
+            0.25 * std::f32::consts::FRAC_1_PI,
+        ))
+    }
+
+    fn pdf_le(&self, _direction: &Vec3) -> f32 {
+        0.25 * std::f32::consts::FRAC_1_PI
+    }
+
+    fn falloff(&self) -> f32 {
+        self.falloff
+    }
+}
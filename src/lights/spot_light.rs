@@ -0,0 +1,133 @@
+use crate::geometry::ray::Ray;
+use crate::graphics::pixel::Pixel;
+use crate::lights::{
+    ies_profile::IesProfile,
+    light::{Light, LightEmission, LightSample},
+};
+use crate::math::vec3::Vec3;
+use crate::sampling::sampler::Sampler;
+
+use std::sync::Arc;
+
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub intensity: Pixel,
+    pub falloff: f32,
+    pub profile: Option<Arc<IesProfile>>,
+    cos_cone: f32,
+    cos_edge: f32,
+}
+
+impl SpotLight {
+    // The cone angle is the half-angle of the spot in degrees; the soft edge is
+    // the angular width over which the intensity fades out towards the cone.
+    pub fn new(
+        position: Vec3,
+        direction: Vec3,
+        intensity: Pixel,
+        cone_angle: f32,
+        soft_edge: f32,
+    ) -> Self {
+        let soft_edge = soft_edge.clamp(0.0, cone_angle);
+        Self {
+            position,
+            direction: direction.unit_vector(),
+            intensity,
+            falloff: 2.0,
+            profile: None,
+            cos_cone: cone_angle.to_radians().cos(),
+            cos_edge: (cone_angle - soft_edge).to_radians().cos(),
+        }
+    }
+
+    pub fn with_falloff(mut self, falloff: f32) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    // The profile's nadir is aligned with the spot direction.
+    pub fn with_profile(mut self, profile: Arc<IesProfile>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    fn profile_reference(&self) -> Vec3 {
+        let axis = match self.direction.x.abs() > 0.9 {
+            true => Vec3::new(0.0, 0.0, 1.0),
+            false => Vec3::new(1.0, 0.0, 0.0),
+        };
+        (&axis - self.direction.dot(&axis) * &self.direction).unit_vector()
+    }
+
+    fn attenuation(&self, direction: &Vec3) -> f32 {
+        let mut attenuation = self.cone_attenuation(self.direction.dot(direction));
+        if let Some(profile) = &self.profile {
+            attenuation *=
+                profile.relative_intensity(direction, &self.direction, &self.profile_reference());
+        }
+        attenuation
+    }
+
+    fn cone_attenuation(&self, cos_theta: f32) -> f32 {
+        if cos_theta >= self.cos_edge {
+            return 1.0;
+        }
+        if cos_theta <= self.cos_cone {
+            return 0.0;
+        }
+        let t = (cos_theta - self.cos_cone) / (self.cos_edge - self.cos_cone);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample_li(&self, point: &Vec3) -> Option<LightSample> {
+        let to_light = &self.position - point;
+        let distance = to_light.norm();
+        if distance <= 0.0 {
+            return None;
+        }
+
+        let direction = to_light / distance;
+        let attenuation = self.attenuation(&-&direction);
+        if attenuation <= 0.0 {
+            return None;
+        }
+
+        let radiance = attenuation * distance.powf(-self.falloff) * self.intensity.clone();
+        Some(LightSample::new(direction, distance, radiance))
+    }
+
+    // Directions are sampled uniformly within the cone.
+    fn sample_le(&self, sampler: &mut dyn Sampler) -> Option<LightEmission> {
+        let (u, v) = sampler.get_2d();
+        let cos_theta = 1.0 - u * (1.0 - self.cos_cone);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * std::f32::consts::PI * v;
+
+        let u = self.profile_reference();
+        let v = self.direction.cross(&u);
+        let direction =
+            sin_theta * phi.cos() * &u + sin_theta * phi.sin() * &v + cos_theta * &self.direction;
+
+        let intensity = self.attenuation(&direction) * self.intensity.clone();
+        let pdf = self.pdf_le(&direction);
+        Some(LightEmission::new(
+            Ray::new(self.position.clone(), direction),
+            intensity,
+            pdf,
+        ))
+    }
+
+    fn pdf_le(&self, direction: &Vec3) -> f32 {
+        match self.direction.dot(direction) >= self.cos_cone {
+            true => 0.5 * std::f32::consts::FRAC_1_PI / (1.0 - self.cos_cone),
+            false => 0.0,
+        }
+    }
+
+    fn falloff(&self) -> f32 {
+        self.falloff
+    }
+}
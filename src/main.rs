@@ -1,16 +1,23 @@
+pub mod filters;
 pub mod geometry;
 pub mod graphics;
+pub mod integrators;
+pub mod lights;
 pub mod materials;
 pub mod math;
+pub mod sampling;
+pub mod spectral;
 
 use geometry::{hittable_list::HittableList, sphere::Sphere};
-use graphics::{pixel::Pixel, renderer::RendererBuilder};
-use materials::material::Material;
+use graphics::{
+    cancellation::CancellationToken, pixel::Pixel, renderer::RendererBuilder, scene::Scene,
+};
+use lights::light_list::LightList;
+use materials::{material::Material, refractive_index::RefractiveIndex};
 use math::vec3::Vec3;
+use sampling::{independent_sampler::IndependentSampler, sampler::Sampler};
 
-use rand::Rng;
-
-fn build_sphere_world() -> HittableList {
+fn build_sphere_world(seed: u64) -> HittableList {
     let mut world = HittableList::new();
 
     let ground_material = Material::Lambertian(Pixel::from(&Vec3::new(0.5, 0.5, 0.5)));
@@ -20,33 +27,35 @@ fn build_sphere_world() -> HittableList {
         ground_material,
     )));
 
-    let mut rng = rand::thread_rng();
+    let mut sampler = IndependentSampler::new(seed);
 
-    (-11..11).into_iter().for_each(|a| {
-        (-11..11).into_iter().for_each(|b| {
-            let choose_material = rng.gen::<f32>();
+    (-11..11).for_each(|a| {
+        (-11..11).for_each(|b| {
+            let choose_material = sampler.get_1d();
             let center = Vec3::new(
-                a as f32 + 0.9 * rng.gen::<f32>(),
+                a as f32 + 0.9 * sampler.get_1d(),
                 0.2,
-                b as f32 + 0.9 * rng.gen::<f32>(),
+                b as f32 + 0.9 * sampler.get_1d(),
             );
 
             if (&center - Vec3::new(4.0, 0.2, 0.0)).norm() > 0.9 {
                 match choose_material {
                     x if (0.0..0.8).contains(&x) => {
-                        let albedo =
-                            Pixel::from(&(Vec3::random(0.0, 1.0) * Vec3::random(0.0, 1.0)));
+                        let albedo = Pixel::from(
+                            &(Vec3::random(0.0, 1.0, &mut sampler)
+                                * Vec3::random(0.0, 1.0, &mut sampler)),
+                        );
                         let material = Material::Lambertian(albedo);
                         world.push(Box::new(Sphere::new(center, 0.2, material)));
                     }
                     x if (0.8..0.95).contains(&x) => {
-                        let albedo = Pixel::from(&Vec3::random(0.5, 1.0));
-                        let fuzz = rng.gen_range(0.0..0.5);
-                        let material = Material::Metal(albedo, fuzz);
+                        let albedo = Pixel::from(&Vec3::random(0.5, 1.0, &mut sampler));
+                        let fuzz = 0.5 * sampler.get_1d();
+                        let material = Material::Metal(albedo, fuzz, None);
                         world.push(Box::new(Sphere::new(center, 0.2, material)));
                     }
                     _ => {
-                        let material = Material::Dielectric(1.5);
+                        let material = Material::Dielectric(RefractiveIndex::Constant(1.5), None);
                         world.push(Box::new(Sphere::new(center, 0.2, material)));
                     }
                 }
@@ -54,7 +63,7 @@ fn build_sphere_world() -> HittableList {
         });
     });
 
-    let material = Material::Dielectric(1.5);
+    let material = Material::Dielectric(RefractiveIndex::Constant(1.5), None);
     world.push(Box::new(Sphere::new(
         Vec3::new(0.0, 1.0, 0.0),
         1.0,
@@ -68,7 +77,7 @@ fn build_sphere_world() -> HittableList {
         material,
     )));
 
-    let material = Material::Metal(Pixel::from(&Vec3::new(0.7, 0.6, 0.5)), 0.0);
+    let material = Material::Metal(Pixel::from(&Vec3::new(0.7, 0.6, 0.5)), 0.0, None);
     world.push(Box::new(Sphere::new(
         Vec3::new(4.0, 1.0, 0.0),
         1.0,
@@ -78,14 +87,24 @@ fn build_sphere_world() -> HittableList {
     world
 }
 
-fn main() -> Result<(), Box<(dyn std::error::Error + 'static)>> {
+fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
     let path = "/home/felix/Projects/raytracing_in_a_weekend/results/sphere_world.ppm";
 
-    let world = build_sphere_world();
+    let seed = 42;
+
+    let scene = Scene::new(build_sphere_world(seed), LightList::new());
+
+    // Ctrl-C finishes the current pass and writes the image as it stands.
+    let cancellation = CancellationToken::new();
+    let token = cancellation.clone();
+    ctrlc::set_handler(move || token.cancel())?;
 
-    let renderer = RendererBuilder::default().build();
+    let mut renderer = RendererBuilder::default()
+        .with_seed(seed)
+        .with_cancellation(cancellation)
+        .build();
 
-    renderer.render(path, &world);
+    renderer.render(path, &scene)?;
 
     Ok(())
 }
@@ -1,2 +1,7 @@
+pub mod image_texture;
 pub mod material;
+pub mod refractive_index;
 pub mod scatter;
+pub mod shading_map;
+pub mod texture;
+pub mod thin_film;
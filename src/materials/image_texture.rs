@@ -0,0 +1,125 @@
+use crate::graphics::{color_space::ColorSpace, pixel::Pixel};
+
+use std::fs;
+use std::io::{Error, ErrorKind};
+
+pub struct ImageTexture {
+    width: u32,
+    height: u32,
+    data: Vec<Pixel>,
+}
+
+impl ImageTexture {
+    pub fn new(width: u32, height: u32, data: Vec<Pixel>) -> Self {
+        assert_eq!(data.len(), (width * height) as usize);
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    pub fn from_ppm(path: &str) -> std::io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let mut cursor = 0;
+
+        let magic = ImageTexture::next_token(&bytes, &mut cursor)?;
+        let width = ImageTexture::next_number(&bytes, &mut cursor)?;
+        let height = ImageTexture::next_number(&bytes, &mut cursor)?;
+        let max_val = ImageTexture::next_number(&bytes, &mut cursor)?;
+        if width == 0 || height == 0 || max_val == 0 || max_val > 65535 {
+            return Err(ImageTexture::invalid("unsupported PPM dimensions or depth"));
+        }
+
+        let n_values = (3 * width * height) as usize;
+        let values = match magic.as_str() {
+            "P3" => (0..n_values)
+                .map(|_| ImageTexture::next_number(&bytes, &mut cursor))
+                .collect::<std::io::Result<Vec<u32>>>()?,
+            "P6" => {
+                let raster = &bytes[(cursor + 1).min(bytes.len())..];
+                let n_bytes = if max_val < 256 { 1 } else { 2 };
+                if raster.len() < n_bytes * n_values {
+                    return Err(ImageTexture::invalid("truncated PPM raster"));
+                }
+                raster
+                    .chunks_exact(n_bytes)
+                    .take(n_values)
+                    .map(|chunk| chunk.iter().fold(0, |acc, &b| (acc << 8) | b as u32))
+                    .collect()
+            }
+            _ => return Err(ImageTexture::invalid("not a PPM file")),
+        };
+
+        let scale = 1.0 / max_val as f32;
+        let data = values
+            .chunks_exact(3)
+            .map(|rgb| Pixel {
+                r: scale * rgb[0] as f32,
+                g: scale * rgb[1] as f32,
+                b: scale * rgb[2] as f32,
+            })
+            .collect();
+
+        Ok(ImageTexture::new(width, height, data))
+    }
+
+    // Texels are converted up front, so that filtering happens in the linear
+    // working space rather than on encoded values.
+    pub fn convert(&self, from: ColorSpace, to: ColorSpace) -> Self {
+        let data = self
+            .data
+            .iter()
+            .map(|texel| from.convert(texel, to))
+            .collect();
+        ImageTexture::new(self.width, self.height, data)
+    }
+
+    pub fn value(&self, u: f32, v: f32) -> Pixel {
+        let x = u.clamp(0.0, 1.0) * self.width as f32 - 0.5;
+        let y = (1.0 - v.clamp(0.0, 1.0)) * self.height as f32 - 0.5;
+
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let texel = |i: f32, j: f32| -> Pixel {
+            let i = (i.max(0.0) as u32).min(self.width - 1);
+            let j = (j.max(0.0) as u32).min(self.height - 1);
+            self.data[(j * self.width + i) as usize].clone()
+        };
+
+        (1.0 - ty) * ((1.0 - tx) * texel(x0, y0) + tx * texel(x0 + 1.0, y0))
+            + ty * ((1.0 - tx) * texel(x0, y0 + 1.0) + tx * texel(x0 + 1.0, y0 + 1.0))
+    }
+
+    fn next_token(bytes: &[u8], cursor: &mut usize) -> std::io::Result<String> {
+        loop {
+            match bytes.get(*cursor) {
+                Some(b'#') => {
+                    while !matches!(bytes.get(*cursor), Some(b'\n') | None) {
+                        *cursor += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => *cursor += 1,
+                Some(_) => break,
+                None => return Err(ImageTexture::invalid("unexpected end of PPM file")),
+            }
+        }
+
+        let start = *cursor;
+        while matches!(bytes.get(*cursor), Some(b) if !b.is_ascii_whitespace()) {
+            *cursor += 1;
+        }
+        Ok(String::from_utf8_lossy(&bytes[start..*cursor]).into_owned())
+    }
+
+    fn next_number(bytes: &[u8], cursor: &mut usize) -> std::io::Result<u32> {
+        ImageTexture::next_token(bytes, cursor)?
+            .parse::<u32>()
+            .map_err(|_| ImageTexture::invalid("malformed number in PPM file"))
+    }
+
+    fn invalid(msg: &str) -> Error {
+        Error::new(ErrorKind::InvalidData, msg)
+    }
+}
@@ -1,33 +1,268 @@
-use crate::geometry::{hit_record::{FacingDirection, HitRecord}, ray::Ray};
-use crate::graphics::pixel::Pixel;
-use crate::materials::scatter::{Scatter, ScatterMode, ScatterResult};
+use crate::geometry::{
+    hit_record::{FacingDirection, HitRecord},
+    ray::Ray,
+};
+use crate::graphics::{color_space::ColorSpace, pixel::Pixel};
+use crate::materials::{
+    refractive_index::RefractiveIndex,
+    scatter::{Scatter, ScatterMode, ScatterResult, SpectralScatterResult},
+    shading_map::ShadingMap,
+    texture::Texture,
+    thin_film::{ThinFilm, RGB_WAVELENGTHS},
+};
 use crate::math::vec3::Vec3;
+use crate::sampling::{
+    hash::{hash_float, hash_u64},
+    sampler::Sampler,
+};
+use crate::spectral::{sampled_spectrum::SampledSpectrum, sampled_wavelengths::SampledWavelengths};
 
-use rand::Rng;
+use num::complex::Complex32;
 
 #[derive(Clone)]
 pub enum Material {
     Lambertian(Pixel),
-    Metal(Pixel, f32),
-    Dielectric(f32),
+    OrenNayar(Pixel, f32),
+    Metal(Pixel, f32, Option<ThinFilm>),
+    Dielectric(RefractiveIndex, Option<ThinFilm>),
+    Mapped(Box<Material>, ShadingMap),
+    Masked(Box<Material>, Texture),
 }
 
 impl Scatter for Material {
-    fn scatter(ray: &Ray, record: &HitRecord) -> Option<ScatterResult> {
-        match record.material.clone() {
-            Material::Lambertian(albedo) => Material::lambertian_impl(record, albedo),
-            Material::Metal(albedo, fuzz) => Material::metal_impl(ray, record, albedo, fuzz),
-            Material::Dielectric(refr_index) => Material::dielectric_impl(ray, record, refr_index),
+    fn scatter(ray: &Ray, record: &HitRecord, sampler: &mut dyn Sampler) -> Option<ScatterResult> {
+        record.material.scatter_surface(ray, record, sampler)
+    }
+
+    fn scatter_spectral(
+        ray: &Ray,
+        record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        color_space: ColorSpace,
+        sampler: &mut dyn Sampler,
+    ) -> Option<SpectralScatterResult> {
+        record
+            .material
+            .scatter_surface_spectral(ray, record, wavelengths, color_space, sampler)
+    }
+
+    fn eval(ray: &Ray, record: &HitRecord, direction: &Vec3) -> Pixel {
+        record.material.eval_surface(ray, record, direction)
+    }
+
+    fn pdf(_ray: &Ray, record: &HitRecord, direction: &Vec3) -> f32 {
+        match record.material.is_specular() {
+            true => 0.0,
+            false => {
+                let cos_theta = record.shading_normal.dot(&direction.unit_vector());
+                cos_theta.max(0.0) * std::f32::consts::FRAC_1_PI
+            }
         }
     }
 }
 
 impl Material {
-    fn lambertian_impl(record: &HitRecord, albedo: Pixel) -> Option<ScatterResult> {
-        let mut scatter_direction = &record.normal + Vec3::random_unit_vector();
+    pub fn with_normal_map(self, texture: Texture) -> Self {
+        Material::Mapped(Box::new(self), ShadingMap::Normal(texture))
+    }
+
+    pub fn with_bump_map(self, texture: Texture, scale: f32) -> Self {
+        Material::Mapped(Box::new(self), ShadingMap::Bump(texture, scale))
+    }
+
+    pub fn with_opacity(self, texture: Texture) -> Self {
+        Material::Masked(Box::new(self), texture)
+    }
+
+    pub fn shading_normal(&self, record: &HitRecord) -> Vec3 {
+        match self {
+            Material::Mapped(material, map) => {
+                map.shading_normal(record, &material.shading_normal(record))
+            }
+            Material::Masked(material, _) => material.shading_normal(record),
+            _ => record.shading_normal.clone(),
+        }
+    }
+
+    pub fn opacity(&self, (u, v): (f32, f32)) -> f32 {
+        match self {
+            Material::Masked(material, texture) => {
+                texture.scalar_value(u, v).clamp(0.0, 1.0) * material.opacity((u, v))
+            }
+            Material::Mapped(material, _) => material.opacity((u, v)),
+            _ => 1.0,
+        }
+    }
+
+    // Specular materials scatter into directions that cannot be evaluated or
+    // connected to.
+    pub fn is_specular(&self) -> bool {
+        match self {
+            Material::Lambertian(..) | Material::OrenNayar(..) => false,
+            Material::Metal(..) | Material::Dielectric(..) => true,
+            Material::Mapped(material, _) | Material::Masked(material, _) => material.is_specular(),
+        }
+    }
+
+    // Reflectance at normal incidence as seen by denoisers and compositing.
+    pub fn albedo(&self) -> Pixel {
+        match self {
+            Material::Lambertian(albedo)
+            | Material::OrenNayar(albedo, _)
+            | Material::Metal(albedo, ..) => albedo.clone(),
+            Material::Dielectric(..) => Pixel::from(&Vec3::new(1.0, 1.0, 1.0)),
+            Material::Mapped(material, _) | Material::Masked(material, _) => material.albedo(),
+        }
+    }
+
+    // Identifier derived from the material's parameters, so that equal
+    // materials share an ID. It fits 24 bits to be stored exactly as a float.
+    pub fn id(&self) -> u32 {
+        let film = |film: &Option<ThinFilm>| film.as_ref().map_or(0.0, |f| f.refraction_index);
+        let key = match self {
+            Material::Lambertian(a) => vec![0.0, a.r, a.g, a.b],
+            Material::OrenNayar(a, sigma) => vec![1.0, a.r, a.g, a.b, *sigma],
+            Material::Metal(a, fuzz, coating) => vec![2.0, a.r, a.g, a.b, *fuzz, film(coating)],
+            Material::Dielectric(index, coating) => {
+                vec![3.0, index.reference(), index.at(450.0), film(coating)]
+            }
+            Material::Mapped(material, _) => vec![4.0, material.id() as f32],
+            Material::Masked(material, _) => vec![5.0, material.id() as f32],
+        };
+        let bits = key.iter().map(|v| v.to_bits() as u64).collect::<Vec<_>>();
+        (hash_u64(&bits) >> 40) as u32
+    }
+
+    // Partially transparent surfaces are hit with probability equal to their opacity.
+    // The stochastic test hashes the ray and hit point rather than drawing
+    // from a sampler, so that intersection stays deterministic.
+    pub fn is_opaque_at(&self, uv: (f32, f32), point: &Vec3, ray: &Ray) -> bool {
+        match self.opacity(uv) {
+            alpha if alpha >= 1.0 => true,
+            alpha if alpha <= 0.0 => false,
+            alpha => {
+                let (o, d) = (ray.origin(), ray.direction());
+                alpha > hash_float(&[o.x, o.y, o.z, d.x, d.y, d.z, point.x, point.y, point.z])
+            }
+        }
+    }
+
+    // BSDF times cosine for a given outgoing direction. Specular materials
+    // cannot be evaluated for an arbitrary direction and contribute nothing.
+    fn eval_surface(&self, ray: &Ray, record: &HitRecord, direction: &Vec3) -> Pixel {
+        let wi = direction.unit_vector();
+        let cos_theta = record.shading_normal.dot(&wi);
+        if cos_theta <= 0.0 || record.normal.dot(&wi) <= 0.0 {
+            return Pixel::default();
+        }
+
+        match self {
+            Material::Lambertian(albedo) => {
+                cos_theta * std::f32::consts::FRAC_1_PI * albedo.clone()
+            }
+            Material::OrenNayar(albedo, sigma) => {
+                let wo = -ray.direction().unit_vector();
+                let weight = Material::oren_nayar_weight(&wo, &wi, &record.shading_normal, *sigma);
+                weight * cos_theta * std::f32::consts::FRAC_1_PI * albedo.clone()
+            }
+            Material::Mapped(material, _) | Material::Masked(material, _) => {
+                material.eval_surface(ray, record, direction)
+            }
+            Material::Metal(..) | Material::Dielectric(..) => Pixel::default(),
+        }
+    }
+
+    fn scatter_surface(
+        &self,
+        ray: &Ray,
+        record: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterResult> {
+        match self.clone() {
+            Material::Lambertian(albedo) => Material::lambertian_impl(record, albedo, sampler),
+            Material::OrenNayar(albedo, sigma) => {
+                Material::oren_nayar_impl(ray, record, albedo, sigma, sampler)
+            }
+            Material::Metal(albedo, fuzz, None) => {
+                Material::metal_impl(ray, record, albedo, fuzz, sampler)
+            }
+            Material::Metal(albedo, fuzz, Some(film)) => {
+                Material::coated_metal_impl(ray, record, albedo, fuzz, &film, sampler)
+            }
+            Material::Dielectric(refr_index, None) => {
+                Material::dielectric_impl(ray, record, refr_index.reference(), sampler)
+            }
+            Material::Dielectric(refr_index, Some(film)) => Material::coated_dielectric_impl(
+                ray,
+                record,
+                refr_index.reference(),
+                &film,
+                sampler,
+            ),
+            Material::Mapped(material, _) | Material::Masked(material, _) => {
+                material.scatter_surface(ray, record, sampler)
+            }
+        }
+    }
+
+    fn scatter_surface_spectral(
+        &self,
+        ray: &Ray,
+        record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        color_space: ColorSpace,
+        sampler: &mut dyn Sampler,
+    ) -> Option<SpectralScatterResult> {
+        match self.clone() {
+            Material::Metal(albedo, fuzz, Some(film)) => Material::coated_metal_spectral_impl(
+                ray,
+                record,
+                color_space.convert(&albedo, ColorSpace::LinearSrgb),
+                fuzz,
+                &film,
+                wavelengths,
+                sampler,
+            ),
+            Material::Dielectric(refr_index, coating) => {
+                if refr_index.is_dispersive() {
+                    wavelengths.terminate_secondary();
+                }
+                match coating {
+                    Some(film) => Material::coated_dielectric_spectral_impl(
+                        ray,
+                        record,
+                        &refr_index,
+                        &film,
+                        wavelengths,
+                        sampler,
+                    ),
+                    None => Material::dielectric_impl(
+                        ray,
+                        record,
+                        refr_index.at(wavelengths.hero()),
+                        sampler,
+                    )
+                    .map(|res| res.to_spectral(color_space, wavelengths)),
+                }
+            }
+            Material::Mapped(material, _) | Material::Masked(material, _) => {
+                material.scatter_surface_spectral(ray, record, wavelengths, color_space, sampler)
+            }
+            _ => self
+                .scatter_surface(ray, record, sampler)
+                .map(|res| res.to_spectral(color_space, wavelengths)),
+        }
+    }
+
+    fn lambertian_impl(
+        record: &HitRecord,
+        albedo: Pixel,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterResult> {
+        let mut scatter_direction = &record.shading_normal + Vec3::random_unit_vector(sampler);
 
         if scatter_direction.near_zero() {
-            scatter_direction = record.normal.clone();
+            scatter_direction = record.shading_normal.clone();
         }
 
         Some(ScatterResult::new(
@@ -36,15 +271,64 @@ impl Material {
         ))
     }
 
+    fn oren_nayar_impl(
+        ray: &Ray,
+        record: &HitRecord,
+        albedo: Pixel,
+        sigma: f32,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterResult> {
+        let lambertian = Material::lambertian_impl(record, albedo, sampler)?;
+        let n = &record.shading_normal;
+        let wo = -ray.direction().unit_vector();
+        let wi = lambertian.ray.direction().unit_vector();
+
+        let weight = Material::oren_nayar_weight(&wo, &wi, n, sigma);
+        Some(ScatterResult::new(
+            lambertian.ray,
+            weight * lambertian.attenuation,
+        ))
+    }
+
+    // Ratio of the Oren-Nayar BRDF to the Lambertian one for a roughness given in degrees.
+    fn oren_nayar_weight(wo: &Vec3, wi: &Vec3, n: &Vec3, sigma: f32) -> f32 {
+        let sigma = sigma.to_radians();
+        let sigma2 = sigma * sigma;
+        let a = 1.0 - sigma2 / (2.0 * (sigma2 + 0.33));
+        let b = 0.45 * sigma2 / (sigma2 + 0.09);
+
+        let cos_i = n.dot(wi).clamp(-1.0, 1.0);
+        let cos_o = n.dot(wo).clamp(-1.0, 1.0);
+        let sin_i = (1.0 - cos_i * cos_i).max(0.0).sqrt();
+        let sin_o = (1.0 - cos_o * cos_o).max(0.0).sqrt();
+
+        let max_cos = match sin_i > 1e-4 && sin_o > 1e-4 {
+            true => {
+                let proj_i = (wi - cos_i * n).unit_vector();
+                let proj_o = (wo - cos_o * n).unit_vector();
+                proj_i.dot(&proj_o).max(0.0)
+            }
+            false => 0.0,
+        };
+
+        let (sin_alpha, tan_beta) = match cos_i.abs() > cos_o.abs() {
+            true => (sin_o, sin_i / cos_i.abs()),
+            false => (sin_i, sin_o / cos_o.abs().max(1e-4)),
+        };
+
+        a + b * max_cos * sin_alpha * tan_beta
+    }
+
     fn metal_impl(
         ray: &Ray,
         record: &HitRecord,
         albedo: Pixel,
         fuzz: f32,
+        sampler: &mut dyn Sampler,
     ) -> Option<ScatterResult> {
         let mut reflect_direction =
-            Material::reflect(&ray.direction().unit_vector(), &record.normal);
-        reflect_direction += fuzz.clamp(0.0, 1.0) * Vec3::random_unit_vector();
+            Material::reflect(&ray.direction().unit_vector(), &record.shading_normal);
+        reflect_direction += fuzz.clamp(0.0, 1.0) * Vec3::random_unit_vector(sampler);
 
         let ray = Ray::new(record.point.clone(), reflect_direction);
 
@@ -58,6 +342,7 @@ impl Material {
         ray: &Ray,
         record: &HitRecord,
         refraction_index: f32,
+        sampler: &mut dyn Sampler,
     ) -> Option<ScatterResult> {
         let attenuation = Pixel::from(&Vec3::new(1.0, 1.0, 1.0));
         let refraction_ratio = match record.facing {
@@ -66,18 +351,19 @@ impl Material {
         };
         let unit_direction = &ray.direction().unit_vector();
 
-        let cos_theta = record.normal.dot(&-unit_direction).min(1.0);
+        let cos_theta = record.shading_normal.dot(&-unit_direction).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
-        match Material::scatter_mode(sin_theta, cos_theta, refraction_ratio) {
+        let u = sampler.get_1d();
+        match Material::scatter_mode(sin_theta, cos_theta, refraction_ratio, u) {
             ScatterMode::Reflect => {
-                let direction = Material::reflect(&unit_direction, &record.normal);
+                let direction = Material::reflect(unit_direction, &record.shading_normal);
                 let ray = Ray::new(record.point.clone(), direction);
                 Some(ScatterResult::new(ray, attenuation))
             }
             ScatterMode::Refract => {
                 let direction =
-                    Material::refract(&unit_direction, &record.normal, refraction_ratio);
+                    Material::refract(unit_direction, &record.shading_normal, refraction_ratio);
                 let ray = Ray::new(record.point.clone(), direction);
                 Some(ScatterResult::new(ray, attenuation))
             }
@@ -85,17 +371,220 @@ impl Material {
         }
     }
 
+    fn coated_metal_impl(
+        ray: &Ray,
+        record: &HitRecord,
+        albedo: Pixel,
+        fuzz: f32,
+        film: &ThinFilm,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterResult> {
+        let cos_theta = Material::cos_incident(ray, record);
+        let thickness = film.thickness_at(record);
+        let reflectivity = [albedo.r, albedo.g, albedo.b];
+        let [r, g, b] = std::array::from_fn(|i| {
+            let substrate_index = ThinFilm::conductor_index(reflectivity[i]);
+            film.reflectance(
+                thickness,
+                cos_theta,
+                1.0,
+                substrate_index,
+                RGB_WAVELENGTHS[i],
+            )
+        });
+
+        Material::metal_impl(ray, record, albedo, fuzz, sampler)
+            .map(|res| ScatterResult::new(res.ray, Pixel { r, g, b }))
+    }
+
+    // The albedo is given in linear sRGB.
+    fn coated_metal_spectral_impl(
+        ray: &Ray,
+        record: &HitRecord,
+        albedo: Pixel,
+        fuzz: f32,
+        film: &ThinFilm,
+        wavelengths: &SampledWavelengths,
+        sampler: &mut dyn Sampler,
+    ) -> Option<SpectralScatterResult> {
+        let cos_theta = Material::cos_incident(ray, record);
+        let thickness = film.thickness_at(record);
+        let reflectivity = SampledSpectrum::from_rgb(&albedo, ColorSpace::LinearSrgb, wavelengths);
+        let mut values = *reflectivity.values();
+        values
+            .iter_mut()
+            .zip(wavelengths.lambda().iter())
+            .for_each(|(val, &lambda)| {
+                let substrate_index = ThinFilm::conductor_index(*val);
+                *val = film.reflectance(thickness, cos_theta, 1.0, substrate_index, lambda);
+            });
+
+        Material::metal_impl(ray, record, albedo, fuzz, sampler)
+            .map(|res| SpectralScatterResult::new(res.ray, SampledSpectrum::new(values)))
+    }
+
+    fn coated_dielectric_impl(
+        ray: &Ray,
+        record: &HitRecord,
+        refraction_index: f32,
+        film: &ThinFilm,
+        sampler: &mut dyn Sampler,
+    ) -> Option<ScatterResult> {
+        let cos_theta = Material::cos_incident(ray, record);
+        let thickness = film.thickness_at(record);
+        let (incident_index, substrate_index) =
+            Material::interface_indices(record, refraction_index);
+        let [r, g, b] = RGB_WAVELENGTHS.map(|lambda| {
+            let substrate_index = Complex32::new(substrate_index, 0.0);
+            film.reflectance(
+                thickness,
+                cos_theta,
+                incident_index,
+                substrate_index,
+                lambda,
+            )
+        });
+        let reflect_probability = Material::clamp_probability((r + g + b) / 3.0);
+
+        let (mode, ray) = Material::split_interface(
+            ray,
+            record,
+            incident_index / substrate_index,
+            reflect_probability,
+            sampler,
+        );
+        match mode {
+            ScatterMode::Reflect => {
+                let attenuation = 1.0 / reflect_probability * Pixel { r, g, b };
+                Some(ScatterResult::new(ray, attenuation))
+            }
+            ScatterMode::Refract => {
+                let transmittance = Pixel {
+                    r: 1.0 - r,
+                    g: 1.0 - g,
+                    b: 1.0 - b,
+                };
+                let attenuation = 1.0 / (1.0 - reflect_probability) * transmittance;
+                Some(ScatterResult::new(ray, attenuation))
+            }
+            ScatterMode::Absorb => None,
+        }
+    }
+
+    fn coated_dielectric_spectral_impl(
+        ray: &Ray,
+        record: &HitRecord,
+        refraction_index: &RefractiveIndex,
+        film: &ThinFilm,
+        wavelengths: &SampledWavelengths,
+        sampler: &mut dyn Sampler,
+    ) -> Option<SpectralScatterResult> {
+        let cos_theta = Material::cos_incident(ray, record);
+        let thickness = film.thickness_at(record);
+        let reflectance = wavelengths.lambda().map(|lambda| {
+            let (incident_index, substrate_index) =
+                Material::interface_indices(record, refraction_index.at(lambda));
+            let substrate_index = Complex32::new(substrate_index, 0.0);
+            film.reflectance(
+                thickness,
+                cos_theta,
+                incident_index,
+                substrate_index,
+                lambda,
+            )
+        });
+        let reflect_probability =
+            Material::clamp_probability(match wavelengths.secondary_terminated() {
+                true => reflectance[0],
+                false => reflectance.iter().sum::<f32>() / reflectance.len() as f32,
+            });
+
+        let (incident_index, substrate_index) =
+            Material::interface_indices(record, refraction_index.at(wavelengths.hero()));
+        let (mode, ray) = Material::split_interface(
+            ray,
+            record,
+            incident_index / substrate_index,
+            reflect_probability,
+            sampler,
+        );
+        match mode {
+            ScatterMode::Reflect => {
+                let attenuation = reflectance.map(|r| r / reflect_probability);
+                Some(SpectralScatterResult::new(
+                    ray,
+                    SampledSpectrum::new(attenuation),
+                ))
+            }
+            ScatterMode::Refract => {
+                let attenuation = reflectance.map(|r| (1.0 - r) / (1.0 - reflect_probability));
+                Some(SpectralScatterResult::new(
+                    ray,
+                    SampledSpectrum::new(attenuation),
+                ))
+            }
+            ScatterMode::Absorb => None,
+        }
+    }
+
+    // Keeps the branch weights finite when the film reflects everything or
+    // nothing; total internal reflection can still force the reflect branch.
+    fn clamp_probability(probability: f32) -> f32 {
+        probability.clamp(1e-4, 1.0 - 1e-4)
+    }
+
+    fn split_interface(
+        ray: &Ray,
+        record: &HitRecord,
+        refraction_ratio: f32,
+        reflect_probability: f32,
+        sampler: &mut dyn Sampler,
+    ) -> (ScatterMode, Ray) {
+        let unit_direction = &ray.direction().unit_vector();
+        let cos_theta = Material::cos_incident(ray, record);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+        let u = sampler.get_1d();
+        let mode = match Material::cannot_refract(sin_theta, refraction_ratio)
+            || reflect_probability > u
+        {
+            true => ScatterMode::Reflect,
+            false => ScatterMode::Refract,
+        };
+        let direction = match mode {
+            ScatterMode::Refract => {
+                Material::refract(unit_direction, &record.shading_normal, refraction_ratio)
+            }
+            _ => Material::reflect(unit_direction, &record.shading_normal),
+        };
+        (mode, Ray::new(record.point.clone(), direction))
+    }
+
+    fn interface_indices(record: &HitRecord, refraction_index: f32) -> (f32, f32) {
+        match record.facing {
+            FacingDirection::Front => (1.0, refraction_index),
+            FacingDirection::Back => (refraction_index, 1.0),
+        }
+    }
+
+    fn cos_incident(ray: &Ray, record: &HitRecord) -> f32 {
+        record
+            .shading_normal
+            .dot(&-ray.direction().unit_vector())
+            .clamp(0.0, 1.0)
+    }
+
     fn cannot_refract(sine: f32, refraction_index: f32) -> bool {
         refraction_index * sine > 1.0
     }
 
-    fn should_reflect(cosine: f32, refraction_index: f32) -> bool {
-        Material::schlick_reflectance(cosine, refraction_index) > rand::thread_rng().gen::<f32>()
+    fn should_reflect(cosine: f32, refraction_index: f32, u: f32) -> bool {
+        Material::schlick_reflectance(cosine, refraction_index) > u
     }
 
-    fn scatter_mode(sine: f32, cosine: f32, refraction_index: f32) -> ScatterMode {
+    fn scatter_mode(sine: f32, cosine: f32, refraction_index: f32, u: f32) -> ScatterMode {
         if Material::cannot_refract(sine, refraction_index)
-            || Material::should_reflect(cosine, refraction_index)
+            || Material::should_reflect(cosine, refraction_index, u)
         {
             ScatterMode::Reflect
         } else {
@@ -0,0 +1,47 @@
+// Wavelength of the Fraunhofer d line in nm, used whenever no wavelength is sampled.
+pub const REFERENCE_WAVELENGTH: f32 = 587.56;
+
+#[derive(Clone)]
+pub enum RefractiveIndex {
+    Constant(f32),
+    // n = A + B / λ², with B in μm².
+    Cauchy(f32, f32),
+    // n² = 1 + Σ B_i λ² / (λ² - C_i), with C_i in μm².
+    Sellmeier([f32; 3], [f32; 3]),
+}
+
+impl RefractiveIndex {
+    pub fn bk7() -> Self {
+        RefractiveIndex::Sellmeier(
+            [1.039_612, 0.231_792_34, 1.010_469_5],
+            [0.006_000_699, 0.020_017_914, 103.560_65],
+        )
+    }
+
+    pub fn diamond() -> Self {
+        RefractiveIndex::Sellmeier([0.3306, 4.3356, 0.0], [0.030_625, 0.011_236, 0.0])
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, RefractiveIndex::Constant(_))
+    }
+
+    pub fn reference(&self) -> f32 {
+        self.at(REFERENCE_WAVELENGTH)
+    }
+
+    pub fn at(&self, lambda: f32) -> f32 {
+        let lambda_um = 1e-3 * lambda;
+        let lambda_um2 = lambda_um * lambda_um;
+        match self {
+            RefractiveIndex::Constant(n) => *n,
+            RefractiveIndex::Cauchy(a, b) => a + b / lambda_um2,
+            RefractiveIndex::Sellmeier(b, c) => (1.0
+                + b.iter()
+                    .zip(c.iter())
+                    .map(|(b, c)| b * lambda_um2 / (lambda_um2 - c))
+                    .sum::<f32>())
+            .sqrt(),
+        }
+    }
+}
@@ -1,8 +1,20 @@
 use crate::geometry::{hit_record::HitRecord, ray::Ray};
-use crate::graphics::pixel::Pixel;
+use crate::graphics::{color_space::ColorSpace, pixel::Pixel};
+use crate::math::vec3::Vec3;
+use crate::sampling::sampler::Sampler;
+use crate::spectral::{sampled_spectrum::SampledSpectrum, sampled_wavelengths::SampledWavelengths};
 
 pub trait Scatter {
-    fn scatter(ray: &Ray, record: &HitRecord) -> Option<ScatterResult>;
+    fn scatter(ray: &Ray, record: &HitRecord, sampler: &mut dyn Sampler) -> Option<ScatterResult>;
+    fn scatter_spectral(
+        ray: &Ray,
+        record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        color_space: ColorSpace,
+        sampler: &mut dyn Sampler,
+    ) -> Option<SpectralScatterResult>;
+    fn eval(ray: &Ray, record: &HitRecord, direction: &Vec3) -> Pixel;
+    fn pdf(ray: &Ray, record: &HitRecord, direction: &Vec3) -> f32;
 }
 
 pub enum ScatterMode {
@@ -20,4 +32,24 @@ impl ScatterResult {
     pub fn new(ray: Ray, attenuation: Pixel) -> Self {
         Self { ray, attenuation }
     }
+
+    pub fn to_spectral(
+        self,
+        color_space: ColorSpace,
+        wavelengths: &SampledWavelengths,
+    ) -> SpectralScatterResult {
+        let attenuation = SampledSpectrum::from_rgb(&self.attenuation, color_space, wavelengths);
+        SpectralScatterResult::new(self.ray, attenuation)
+    }
+}
+
+pub struct SpectralScatterResult {
+    pub ray: Ray,
+    pub attenuation: SampledSpectrum,
+}
+
+impl SpectralScatterResult {
+    pub fn new(ray: Ray, attenuation: SampledSpectrum) -> Self {
+        Self { ray, attenuation }
+    }
 }
@@ -0,0 +1,70 @@
+use crate::geometry::hit_record::HitRecord;
+use crate::materials::texture::Texture;
+use crate::math::vec3::Vec3;
+
+// Offset in uv space used to difference height maps.
+const BUMP_DELTA: f32 = 5e-4;
+
+#[derive(Clone)]
+pub enum ShadingMap {
+    Normal(Texture),
+    Bump(Texture, f32),
+}
+
+impl ShadingMap {
+    pub fn shading_normal(&self, record: &HitRecord, normal: &Vec3) -> Vec3 {
+        match self {
+            ShadingMap::Normal(texture) => ShadingMap::normal_map_impl(record, normal, texture),
+            ShadingMap::Bump(texture, scale) => {
+                ShadingMap::bump_map_impl(record, normal, texture, *scale)
+            }
+        }
+    }
+
+    fn normal_map_impl(record: &HitRecord, n: &Vec3, texture: &Texture) -> Vec3 {
+        let tangent = match &record.dpdu - n.dot(&record.dpdu) * n {
+            t if t.near_zero() => ShadingMap::any_perpendicular(n),
+            t => t.unit_vector(),
+        };
+        let mut bitangent = n.cross(&tangent);
+        if bitangent.dot(&record.dpdv) < 0.0 {
+            bitangent = -bitangent;
+        }
+
+        let texel = texture.value(record.u, record.v);
+        let normal = (2.0 * texel.r - 1.0) * tangent
+            + (2.0 * texel.g - 1.0) * bitangent
+            + (2.0 * texel.b - 1.0) * n;
+
+        match normal.near_zero() {
+            true => n.clone(),
+            false => normal.unit_vector(),
+        }
+    }
+
+    fn bump_map_impl(record: &HitRecord, n: &Vec3, texture: &Texture, scale: f32) -> Vec3 {
+        let height = |u: f32, v: f32| scale * texture.scalar_value(u, v);
+
+        let displacement = height(record.u, record.v);
+        let du = (height(record.u + BUMP_DELTA, record.v) - displacement) / BUMP_DELTA;
+        let dv = (height(record.u, record.v + BUMP_DELTA) - displacement) / BUMP_DELTA;
+
+        let dpdu = &record.dpdu + du * n;
+        let dpdv = &record.dpdv + dv * n;
+        let normal = dpdu.cross(&dpdv);
+
+        match normal.near_zero() {
+            true => n.clone(),
+            false if normal.dot(n) < 0.0 => -normal.unit_vector(),
+            false => normal.unit_vector(),
+        }
+    }
+
+    fn any_perpendicular(n: &Vec3) -> Vec3 {
+        let axis = match n.x.abs() > 0.9 {
+            true => Vec3::new(0.0, 1.0, 0.0),
+            false => Vec3::new(1.0, 0.0, 0.0),
+        };
+        n.cross(&axis).unit_vector()
+    }
+}
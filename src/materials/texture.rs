@@ -0,0 +1,38 @@
+use crate::graphics::{color_space::ColorSpace, pixel::Pixel};
+use crate::materials::image_texture::ImageTexture;
+use crate::math::vec3::Vec3;
+
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub enum Texture {
+    Constant(Pixel),
+    Image(Arc<ImageTexture>),
+}
+
+impl Texture {
+    pub fn uniform(val: f32) -> Self {
+        Texture::Constant(Pixel::from(&Vec3::new(val, val, val)))
+    }
+
+    // Declares the colors of the texture to be in `from` and converts them to
+    // the working space `to`.
+    pub fn convert(&self, from: ColorSpace, to: ColorSpace) -> Self {
+        match self {
+            Texture::Constant(color) => Texture::Constant(from.convert(color, to)),
+            Texture::Image(image) => Texture::Image(Arc::new(image.convert(from, to))),
+        }
+    }
+
+    pub fn value(&self, u: f32, v: f32) -> Pixel {
+        match self {
+            Texture::Constant(color) => color.clone(),
+            Texture::Image(image) => image.value(u, v),
+        }
+    }
+
+    pub fn scalar_value(&self, u: f32, v: f32) -> f32 {
+        let color = self.value(u, v);
+        (color.r + color.g + color.b) / 3.0
+    }
+}
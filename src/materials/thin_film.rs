@@ -0,0 +1,84 @@
+use crate::geometry::hit_record::HitRecord;
+use crate::materials::texture::Texture;
+
+use num::complex::Complex32;
+
+// Wavelengths in nm standing in for the red, green and blue channels in RGB mode.
+pub const RGB_WAVELENGTHS: [f32; 3] = [630.0, 532.0, 465.0];
+
+#[derive(Clone)]
+pub struct ThinFilm {
+    pub thickness: Texture,
+    pub refraction_index: f32,
+}
+
+impl ThinFilm {
+    pub fn new(thickness: Texture, refraction_index: f32) -> Self {
+        Self {
+            thickness,
+            refraction_index,
+        }
+    }
+
+    pub fn thickness_at(&self, record: &HitRecord) -> f32 {
+        self.thickness.scalar_value(record.u, record.v).max(0.0)
+    }
+
+    // Airy reflectance of a single film of the given thickness (nm) between an
+    // incident medium and a possibly absorbing substrate, averaged over both
+    // polarizations.
+    pub fn reflectance(
+        &self,
+        thickness: f32,
+        cos_incident: f32,
+        incident_index: f32,
+        substrate_index: Complex32,
+        lambda: f32,
+    ) -> f32 {
+        let n_i = Complex32::new(incident_index, 0.0);
+        let n_f = Complex32::new(self.refraction_index, 0.0);
+        let n_t = substrate_index;
+
+        let cos_i = Complex32::new(cos_incident, 0.0);
+        let sin2_i = Complex32::new(1.0 - cos_incident * cos_incident, 0.0);
+        let cos_f = (Complex32::new(1.0, 0.0) - sin2_i * (n_i / n_f).powi(2)).sqrt();
+        let cos_t = (Complex32::new(1.0, 0.0) - sin2_i * (n_i / n_t).powi(2)).sqrt();
+
+        let phase =
+            Complex32::new(0.0, 4.0 * std::f32::consts::PI * thickness / lambda) * n_f * cos_f;
+        let shift = phase.exp();
+
+        let airy = |r_if: Complex32, r_ft: Complex32| {
+            ((r_if + r_ft * shift) / (Complex32::new(1.0, 0.0) + r_if * r_ft * shift)).norm_sqr()
+        };
+
+        let r_s = airy(
+            ThinFilm::fresnel_s(n_i, cos_i, n_f, cos_f),
+            ThinFilm::fresnel_s(n_f, cos_f, n_t, cos_t),
+        );
+        let r_p = airy(
+            ThinFilm::fresnel_p(n_i, cos_i, n_f, cos_f),
+            ThinFilm::fresnel_p(n_f, cos_f, n_t, cos_t),
+        );
+
+        (0.5 * (r_s + r_p)).clamp(0.0, 1.0)
+    }
+
+    // Complex index of a conductor whose normal-incidence reflectance matches
+    // the given albedo (Gulbrandsen 2014, with the edge tint set to the albedo).
+    pub fn conductor_index(reflectivity: f32) -> Complex32 {
+        let r = reflectivity.clamp(0.0, 0.99);
+        let g = r;
+        let n = g * (1.0 - r) / (1.0 + r) + (1.0 - g) * (1.0 + r.sqrt()) / (1.0 - r.sqrt());
+        let k2 = (r * (n + 1.0).powi(2) - (n - 1.0).powi(2)) / (1.0 - r);
+        Complex32::new(n, k2.max(0.0).sqrt())
+    }
+
+    fn fresnel_s(n_1: Complex32, cos_1: Complex32, n_2: Complex32, cos_2: Complex32) -> Complex32 {
+        (n_1 * cos_1 - n_2 * cos_2) / (n_1 * cos_1 + n_2 * cos_2)
+    }
+
+    fn fresnel_p(n_1: Complex32, cos_1: Complex32, n_2: Complex32, cos_2: Complex32) -> Complex32 {
+        (n_2 * cos_1 - n_1 * cos_2) / (n_2 * cos_1 + n_1 * cos_2)
+    }
+}
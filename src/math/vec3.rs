@@ -1,4 +1,5 @@
-use rand::Rng;
+use crate::sampling::sampler::Sampler;
+
 use std::ops;
 
 #[derive(Default, Debug, Clone)]
@@ -14,48 +15,39 @@ impl Vec3 {
     }
 
     pub fn near_zero(&self) -> bool {
-        let eps = 1e-8 as f32;
+        let eps = 1e-8_f32;
         self.x.abs() < eps && self.y.abs() < eps && self.z.abs() < eps
     }
 
-    pub fn random(lb: f32, ub: f32) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn random(lb: f32, ub: f32, sampler: &mut dyn Sampler) -> Self {
         Self {
-            x: rng.gen_range(lb..=ub),
-            y: rng.gen_range(lb..=ub),
-            z: rng.gen_range(lb..=ub),
+            x: lb + (ub - lb) * sampler.get_1d(),
+            y: lb + (ub - lb) * sampler.get_1d(),
+            z: lb + (ub - lb) * sampler.get_1d(),
         }
     }
 
-    pub fn random_unit_vector() -> Self {
-        Vec3::random_in_unit_sphere().unit_vector()
+    pub fn random_unit_vector(sampler: &mut dyn Sampler) -> Self {
+        let (u, v) = sampler.get_2d();
+        let z = 1.0 - 2.0 * u;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * std::f32::consts::PI * v;
+        Self::new(r * phi.cos(), r * phi.sin(), z)
     }
 
-    pub fn random_on_hemisphere(normal: &Self) -> Self {
-        let random_on_unit_sphere = Vec3::random_unit_vector();
+    pub fn random_on_hemisphere(normal: &Self, sampler: &mut dyn Sampler) -> Self {
+        let random_on_unit_sphere = Vec3::random_unit_vector(sampler);
         match random_on_unit_sphere.dot(normal) {
             x if x > 0.0 => random_on_unit_sphere,
             _ => -random_on_unit_sphere,
         }
     }
 
-    fn random_in_unit_sphere() -> Self {
-        loop {
-            let p = Vec3::random(-1.0, 1.0);
-            if p.norm_squared() < 1.0 {
-                return p;
-            }
-        }
-    }
-
-    pub fn random_in_unit_disk() -> Self {
-        let mut rng = rand::thread_rng();
-        loop {
-            let p = Vec3::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0), 0.0);
-            if p.norm_squared() < 1.0 {
-                return p;
-            }
-        }
+    pub fn random_in_unit_disk(sampler: &mut dyn Sampler) -> Self {
+        let (u, v) = sampler.get_2d();
+        let r = u.sqrt();
+        let phi = 2.0 * std::f32::consts::PI * v;
+        Self::new(r * phi.cos(), r * phi.sin(), 0.0)
     }
 
     pub fn norm_squared(&self) -> f32 {
@@ -0,0 +1,8 @@
+pub mod halton_sampler;
+pub mod hash;
+pub mod independent_sampler;
+pub mod low_discrepancy;
+pub mod metropolis_sampler;
+pub mod sampler;
+pub mod sobol_sampler;
+pub mod stratified_sampler;
@@ -0,0 +1,28 @@
+// Deterministic pseudo-random number in [0, 1) derived from the given values,
+// for decisions that have no sampler at hand, such as stochastic opacity
+// during intersection.
+pub fn hash_float(values: &[f32]) -> f32 {
+    let hash = values.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, value| {
+        mix(hash ^ value.to_bits() as u64)
+    });
+    to_unit_float(hash)
+}
+
+pub fn hash_u64(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, value| mix(hash ^ value))
+}
+
+pub fn to_unit_float(hash: u64) -> f32 {
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+// Finalizer of MurmurHash3.
+pub fn mix(mut v: u64) -> u64 {
+    v ^= v >> 33;
+    v = v.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    v ^= v >> 33;
+    v = v.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    v ^ (v >> 33)
+}
@@ -0,0 +1,50 @@
+use crate::sampling::{
+    hash::{hash_u64, mix},
+    sampler::Sampler,
+};
+
+use rand::Rng;
+use rand_pcg::Pcg32;
+
+// Uniform random numbers from a PCG stream selected by the pixel and seed,
+// advanced to the sample index, so that every pixel sample sees the same
+// numbers regardless of the order in which samples are taken.
+pub struct IndependentSampler {
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl Default for IndependentSampler {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Pcg32::new(mix(seed), seed),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn name(&self) -> &'static str {
+        "independent"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        *self = Self::new(seed);
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        let stream = hash_u64(&[pixel.0 as u64, pixel.1 as u64, self.seed]);
+        self.rng = Pcg32::new(mix(stream), stream);
+        self.rng.advance(index as u64 * 65536);
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        self.rng.gen()
+    }
+}
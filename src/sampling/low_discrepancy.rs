@@ -0,0 +1,103 @@
+use crate::sampling::hash::mix;
+
+pub const ONE_MINUS_EPSILON: f32 = 1.0 - f32::EPSILON / 2.0;
+
+pub const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+// Element `i` of a pseudo-random permutation of 0..length selected by `seed`,
+// after Kensler (2013).
+pub fn permutation_element(mut i: u32, length: u32, seed: u32) -> u32 {
+    let mut w = length.wrapping_sub(1);
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < length {
+            break;
+        }
+    }
+    (i.wrapping_add(seed)) % length
+}
+
+// Radical inverse of `index` in the given prime base with every digit
+// permuted depending on the digits before it, i.e. Owen scrambling.
+pub fn owen_scrambled_radical_inverse(base: u32, mut index: u64, seed: u64) -> f32 {
+    let base_f = base as f32;
+    let inv_base = 1.0 / base_f;
+    let mut reversed_digits = 0u64;
+    let mut inv_base_m = 1.0f32;
+
+    while 1.0 - (base_f - 1.0) * inv_base_m < 1.0 {
+        let next = index / base as u64;
+        let digit = (index - next * base as u64) as u32;
+        let digit_seed = mix(seed ^ reversed_digits) as u32;
+        let digit = permutation_element(digit, base, digit_seed);
+        reversed_digits = reversed_digits * base as u64 + digit as u64;
+        inv_base_m *= inv_base;
+        index = next;
+    }
+
+    (inv_base_m * reversed_digits as f32).min(ONE_MINUS_EPSILON)
+}
+
+// Sobol points of the first two dimensions as 32 bit fractions. The first is
+// the van der Corput sequence and the second is generated by x + 1.
+pub fn sobol_sample(index: u32, dimension: usize) -> u32 {
+    match dimension {
+        0 => index.reverse_bits(),
+        _ => {
+            let mut direction = 1u32 << 31;
+            let mut value = 0u32;
+            let mut index = index;
+            while index != 0 {
+                if index & 1 != 0 {
+                    value ^= direction;
+                }
+                direction ^= direction >> 1;
+                index >>= 1;
+            }
+            value
+        }
+    }
+}
+
+// Owen scrambling of a 32 bit fraction after Laine and Karras (2011).
+pub fn fast_owen_scramble(mut v: u32, seed: u32) -> u32 {
+    v = v.reverse_bits();
+    v ^= v.wrapping_mul(0x3d20_adea);
+    v = v.wrapping_add(seed);
+    v = v.wrapping_mul((seed >> 16) | 1);
+    v ^= v.wrapping_mul(0x0552_6c56);
+    v ^= v.wrapping_mul(0x53a2_2864);
+    v.reverse_bits()
+}
+
+pub fn fraction_to_float(v: u32) -> f32 {
+    (v as f32 * (1.0 / 4_294_967_296.0)).min(ONE_MINUS_EPSILON)
+}
@@ -0,0 +1,173 @@
+use crate::graphics::checkpoint::{self, Accumulator};
+use crate::sampling::{
+    hash::{hash_u64, to_unit_float},
+    sampler::Sampler,
+};
+
+use std::io::{Read, Result, Write};
+
+const ONE_MINUS_EPSILON: f32 = 1.0 - f32::EPSILON / 2.0;
+
+#[derive(Default, Clone)]
+struct PrimarySample {
+    value: f32,
+    last_modification: u64,
+    value_backup: f32,
+    modification_backup: u64,
+}
+
+impl PrimarySample {
+    fn backup(&mut self) {
+        self.value_backup = self.value;
+        self.modification_backup = self.last_modification;
+    }
+
+    fn restore(&mut self) {
+        self.value = self.value_backup;
+        self.last_modification = self.modification_backup;
+    }
+}
+
+// Primary sample space sampler of Kelemen et al. (2002). Each iteration either
+// draws all dimensions afresh (a large step) or perturbs the current ones
+// with a Gaussian of width `sigma`. Dimensions are mutated lazily when they
+// are requested, and a rejected iteration restores those that were touched.
+// Random numbers are hashed from the seed and a running count, so that the
+// whole state of a chain can be saved in a checkpoint.
+pub struct MetropolisSampler {
+    seed: u64,
+    draws: u64,
+    sigma: f32,
+    large_step_probability: f32,
+    samples: Vec<PrimarySample>,
+    sample_index: usize,
+    current_iteration: u64,
+    large_step: bool,
+    last_large_step_iteration: u64,
+}
+
+impl MetropolisSampler {
+    pub fn new(seed: u64, sigma: f32, large_step_probability: f32) -> Self {
+        Self {
+            seed,
+            draws: 0,
+            sigma,
+            large_step_probability,
+            samples: vec![],
+            sample_index: 0,
+            current_iteration: 0,
+            large_step: true,
+            last_large_step_iteration: 0,
+        }
+    }
+
+    pub fn start_iteration(&mut self) {
+        self.current_iteration += 1;
+        self.large_step = self.uniform() < self.large_step_probability;
+        self.sample_index = 0;
+    }
+
+    pub fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step_iteration = self.current_iteration;
+        }
+    }
+
+    pub fn reject(&mut self) {
+        let iteration = self.current_iteration;
+        self.samples
+            .iter_mut()
+            .filter(|sample| sample.last_modification == iteration)
+            .for_each(|sample| sample.restore());
+        self.current_iteration -= 1;
+    }
+
+    fn ensure_ready(&mut self, index: usize) {
+        if index >= self.samples.len() {
+            self.samples.resize(index + 1, PrimarySample::default());
+        }
+
+        // Catch up on a large step that happened since the dimension was last used.
+        if self.samples[index].last_modification < self.last_large_step_iteration {
+            let value = self.uniform();
+            let sample = &mut self.samples[index];
+            sample.value = value;
+            sample.last_modification = self.last_large_step_iteration;
+        }
+
+        self.samples[index].backup();
+        let value = if self.large_step {
+            self.uniform()
+        } else {
+            let small_steps =
+                (self.current_iteration - self.samples[index].last_modification) as f32;
+            let sigma = self.sigma * small_steps.sqrt();
+            let u1 = self.uniform();
+            let u2 = self.uniform();
+            let normal = (-2.0 * (1.0 - u1).ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos();
+            let value = self.samples[index].value + normal * sigma;
+            (value - value.floor()).min(ONE_MINUS_EPSILON)
+        };
+        let sample = &mut self.samples[index];
+        sample.value = value;
+        sample.last_modification = self.current_iteration;
+    }
+
+    fn uniform(&mut self) -> f32 {
+        self.draws += 1;
+        to_unit_float(hash_u64(&[self.seed, self.draws]))
+    }
+}
+
+// The chain's position and random stream; `sigma` and the large step
+// probability are settings and come from the integrator.
+impl Accumulator for MetropolisSampler {
+    fn save(&self, w: &mut dyn Write) -> Result<()> {
+        checkpoint::write_u64(w, self.seed)?;
+        checkpoint::write_u64(w, self.draws)?;
+        checkpoint::write_u64(w, self.current_iteration)?;
+        checkpoint::write_u32(w, self.large_step as u32)?;
+        checkpoint::write_u64(w, self.last_large_step_iteration)?;
+        checkpoint::write_u64(w, self.samples.len() as u64)?;
+        for sample in &self.samples {
+            checkpoint::write_f32(w, sample.value)?;
+            checkpoint::write_u64(w, sample.last_modification)?;
+            checkpoint::write_f32(w, sample.value_backup)?;
+            checkpoint::write_u64(w, sample.modification_backup)?;
+        }
+        Ok(())
+    }
+
+    fn restore(&mut self, r: &mut dyn Read) -> Result<()> {
+        self.seed = checkpoint::read_u64(r)?;
+        self.draws = checkpoint::read_u64(r)?;
+        self.current_iteration = checkpoint::read_u64(r)?;
+        self.large_step = checkpoint::read_u32(r)? != 0;
+        self.last_large_step_iteration = checkpoint::read_u64(r)?;
+        let len = checkpoint::read_u64(r)?;
+        self.samples = (0..len)
+            .map(|_| {
+                Ok(PrimarySample {
+                    value: checkpoint::read_f32(r)?,
+                    last_modification: checkpoint::read_u64(r)?,
+                    value_backup: checkpoint::read_f32(r)?,
+                    modification_backup: checkpoint::read_u64(r)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        self.sample_index = 0;
+        Ok(())
+    }
+}
+
+impl Sampler for MetropolisSampler {
+    fn name(&self) -> &'static str {
+        "metropolis"
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        self.ensure_ready(self.sample_index);
+        self.sample_index += 1;
+        self.samples[self.sample_index - 1].value
+    }
+}
@@ -0,0 +1,24 @@
+// Source of the uniform random numbers that drive rendering. Consumers draw
+// a fixed number of dimensions per decision, so that a sampler can replay or
+// perturb a path by replaying or perturbing its sample stream, and so that
+// the same dimension of every pixel sample feeds the same decision.
+pub trait Sampler {
+    // Identifies the sampler in checkpoints.
+    fn name(&self) -> &'static str;
+
+    // Selects the sequence, so that equal seeds reproduce equal images.
+    fn set_seed(&mut self, _seed: u64) {}
+
+    // Sizes the sequence for the number of samples the renderer takes per
+    // pixel, as samples beyond it wrap around and repeat earlier ones.
+    fn set_samples_per_pixel(&mut self, _samples_per_pixel: u32) {}
+
+    // Restarts the stream at the first dimension of the given pixel sample.
+    fn start_pixel_sample(&mut self, _pixel: (u32, u32), _index: u32) {}
+
+    fn get_1d(&mut self) -> f32;
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        (self.get_1d(), self.get_1d())
+    }
+}
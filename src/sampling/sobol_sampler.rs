@@ -0,0 +1,148 @@
+use crate::sampling::{
+    hash::hash_u64,
+    low_discrepancy::{fast_owen_scramble, fraction_to_float, permutation_element, sobol_sample},
+    sampler::Sampler,
+};
+
+// Padded Sobol sampling: every 1D or 2D request is served by the first one or
+// two Sobol dimensions, Owen scrambled and shuffled per pixel and dimension.
+// Sample counts that are powers of two keep the points fully stratified.
+pub struct SobolSampler {
+    samples_per_pixel: u32,
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new(samples_per_pixel: u32) -> Self {
+        Self {
+            samples_per_pixel: samples_per_pixel.max(1),
+            seed: 0,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next_dimension(&mut self, count: u32) -> (u32, u64) {
+        let hash = hash_u64(&[
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+            self.seed,
+        ]);
+        let spp = self.samples_per_pixel;
+        let index =
+            self.index / spp * spp + permutation_element(self.index % spp, spp, hash as u32);
+        self.dimension += count;
+        (index, hash)
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn name(&self) -> &'static str {
+        "sobol"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    fn set_samples_per_pixel(&mut self, samples_per_pixel: u32) {
+        self.samples_per_pixel = samples_per_pixel.max(1);
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let (index, hash) = self.next_dimension(1);
+        fraction_to_float(fast_owen_scramble(
+            sobol_sample(index, 0),
+            (hash >> 32) as u32,
+        ))
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        let (index, hash) = self.next_dimension(2);
+        (
+            fraction_to_float(fast_owen_scramble(sobol_sample(index, 0), hash as u32)),
+            fraction_to_float(fast_owen_scramble(
+                sobol_sample(index, 1),
+                (hash >> 32) as u32,
+            )),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_lie_in_unit_interval() {
+        let mut sampler = SobolSampler::new(16);
+        sampler.set_seed(3);
+        for pixel in [(0, 0), (7, 3), (1023, 511)] {
+            for index in 0..32 {
+                sampler.start_pixel_sample(pixel, index);
+                for _ in 0..8 {
+                    let u = sampler.get_1d();
+                    let (x, y) = sampler.get_2d();
+                    assert!([u, x, y].iter().all(|v| (0.0..1.0).contains(v)));
+                }
+            }
+        }
+    }
+
+    // For 2^k samples, every grid of 2^a by 2^(k - a) strata gets one point
+    // per stratum.
+    #[test]
+    fn fills_every_stratum_for_powers_of_two() {
+        for log2_count in 0..=6 {
+            let count = 1u32 << log2_count;
+            let mut sampler = SobolSampler::new(count);
+            sampler.set_seed(3);
+            for dimension in 0..4 {
+                let mut points_1d = vec![];
+                let mut points_2d = vec![];
+                for index in 0..count {
+                    sampler.start_pixel_sample((5, 9), index);
+                    (0..dimension).for_each(|_| {
+                        sampler.get_1d();
+                    });
+                    points_1d.push(sampler.get_1d());
+                    points_2d.push(sampler.get_2d());
+                }
+
+                let mut hit = vec![false; count as usize];
+                points_1d
+                    .iter()
+                    .for_each(|u| hit[(u * count as f32) as usize] = true);
+                assert!(hit.iter().all(|&hit| hit), "1D, {} samples", count);
+
+                for log2_columns in 0..=log2_count {
+                    let columns = 1u32 << log2_columns;
+                    let rows = count / columns;
+                    let mut hit = vec![false; count as usize];
+                    points_2d.iter().for_each(|(x, y)| {
+                        let column = (x * columns as f32) as u32;
+                        let row = (y * rows as f32) as u32;
+                        hit[(row * columns + column) as usize] = true;
+                    });
+                    assert!(
+                        hit.iter().all(|&hit| hit),
+                        "2D, {} by {} strata",
+                        columns,
+                        rows
+                    );
+                }
+            }
+        }
+    }
+}
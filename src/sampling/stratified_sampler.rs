@@ -0,0 +1,167 @@
+use crate::sampling::{
+    hash::{hash_u64, to_unit_float},
+    low_discrepancy::{permutation_element, ONE_MINUS_EPSILON},
+    sampler::Sampler,
+};
+
+// Jittered sampling with one stratum per pixel sample. Every dimension visits
+// the strata in its own random order, so that dimensions stay uncorrelated.
+pub struct StratifiedSampler {
+    x_strata: u32,
+    y_strata: u32,
+    jitter: bool,
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new(x_strata: u32, y_strata: u32) -> Self {
+        Self {
+            x_strata: x_strata.max(1),
+            y_strata: y_strata.max(1),
+            jitter: true,
+            seed: 0,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn samples_per_pixel(&self) -> u32 {
+        self.x_strata * self.y_strata
+    }
+
+    fn stratum(&self) -> u32 {
+        let count = self.samples_per_pixel();
+        let hash = hash_u64(&[
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+            self.seed,
+        ]);
+        permutation_element(self.index % count, count, hash as u32)
+    }
+
+    fn offset(&self, axis: u64) -> f32 {
+        if !self.jitter {
+            return 0.5;
+        }
+        to_unit_float(hash_u64(&[
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.index as u64,
+            self.dimension as u64,
+            self.seed,
+            axis,
+        ]))
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn name(&self) -> &'static str {
+        "stratified"
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    // Keeps the grid if it already has one stratum per sample, and otherwise
+    // picks the squarest grid with exactly that many strata.
+    fn set_samples_per_pixel(&mut self, samples_per_pixel: u32) {
+        let count = samples_per_pixel.max(1);
+        if self.samples_per_pixel() == count {
+            return;
+        }
+        self.x_strata = (1..=count.isqrt())
+            .rev()
+            .find(|x| count.is_multiple_of(*x))
+            .unwrap_or(1);
+        self.y_strata = count / self.x_strata;
+    }
+
+    fn start_pixel_sample(&mut self, pixel: (u32, u32), index: u32) {
+        self.pixel = pixel;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f32 {
+        let count = self.samples_per_pixel() as f32;
+        let u = (self.stratum() as f32 + self.offset(0)) / count;
+        self.dimension += 1;
+        u.min(ONE_MINUS_EPSILON)
+    }
+
+    fn get_2d(&mut self) -> (f32, f32) {
+        let stratum = self.stratum();
+        let x = (stratum % self.x_strata) as f32 + self.offset(0);
+        let y = (stratum / self.x_strata) as f32 + self.offset(1);
+        self.dimension += 2;
+        (
+            (x / self.x_strata as f32).min(ONE_MINUS_EPSILON),
+            (y / self.y_strata as f32).min(ONE_MINUS_EPSILON),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampler(samples_per_pixel: u32) -> StratifiedSampler {
+        let mut sampler = StratifiedSampler::new(1, 1);
+        sampler.set_seed(3);
+        sampler.set_samples_per_pixel(samples_per_pixel);
+        sampler
+    }
+
+    #[test]
+    fn samples_lie_in_unit_interval() {
+        let mut sampler = sampler(16);
+        for pixel in [(0, 0), (7, 3), (1023, 511)] {
+            for index in 0..32 {
+                sampler.start_pixel_sample(pixel, index);
+                for _ in 0..8 {
+                    let u = sampler.get_1d();
+                    let (x, y) = sampler.get_2d();
+                    assert!([u, x, y].iter().all(|v| (0.0..1.0).contains(v)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fills_every_stratum_for_powers_of_two() {
+        for count in [1, 2, 4, 8, 16, 32, 64] {
+            let mut sampler = sampler(count);
+            assert_eq!(sampler.samples_per_pixel(), count);
+            for dimension in 0..4 {
+                let mut hit_1d = vec![false; count as usize];
+                let mut hit_2d = vec![false; count as usize];
+                for index in 0..count {
+                    sampler.start_pixel_sample((5, 9), index);
+                    (0..dimension).for_each(|_| {
+                        sampler.get_1d();
+                    });
+                    let u = sampler.get_1d();
+                    hit_1d[(u * count as f32) as usize] = true;
+
+                    let (x, y) = sampler.get_2d();
+                    let column = (x * sampler.x_strata as f32) as u32;
+                    let row = (y * sampler.y_strata as f32) as u32;
+                    hit_2d[(row * sampler.x_strata + column) as usize] = true;
+                }
+                assert!(hit_1d.iter().all(|&hit| hit), "1D, {} samples", count);
+                assert!(hit_2d.iter().all(|&hit| hit), "2D, {} samples", count);
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod color_matching;
+pub mod rgb_uplift;
+pub mod sampled_spectrum;
+pub mod sampled_wavelengths;
@@ -0,0 +1,53 @@
+use crate::graphics::pixel::Pixel;
+use crate::math::vec3::Vec3;
+
+// Integral of the fitted y-bar curve over [LAMBDA_MIN, LAMBDA_MAX].
+pub const CIE_Y_INTEGRAL: f32 = 106.922_07;
+
+// XYZ (D65) to linear sRGB. Emitters are uplifted against D65, so a white
+// light on a white reflector maps to RGB (1, 1, 1).
+const XYZ_TO_LINEAR_SRGB: [[f32; 3]; 3] = [
+    [3.240_97, -1.537_383_2, -0.498_610_8],
+    [-0.969_243_6, 1.875_967_5, 0.041_555_1],
+    [0.055_630_1, -0.203_977, 1.056_971_5],
+];
+
+// Multi-lobe piecewise Gaussian fit of the CIE 1931 2° observer
+// (Wyman, Sloan and Shirley 2013).
+pub fn cie_xyz(lambda: f32) -> Vec3 {
+    Vec3::new(cie_x(lambda), cie_y(lambda), cie_z(lambda))
+}
+
+pub fn cie_x(lambda: f32) -> f32 {
+    1.056 * piecewise_gaussian(lambda, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(lambda, 501.1, 20.4, 26.2)
+}
+
+pub fn cie_y(lambda: f32) -> f32 {
+    0.821 * piecewise_gaussian(lambda, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(lambda, 530.9, 16.3, 31.1)
+}
+
+pub fn cie_z(lambda: f32) -> f32 {
+    1.217 * piecewise_gaussian(lambda, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(lambda, 459.0, 26.0, 13.8)
+}
+
+pub fn xyz_to_linear_srgb(xyz: &Vec3) -> Pixel {
+    let m = &XYZ_TO_LINEAR_SRGB;
+    Pixel {
+        r: m[0][0] * xyz.x + m[0][1] * xyz.y + m[0][2] * xyz.z,
+        g: m[1][0] * xyz.x + m[1][1] * xyz.y + m[1][2] * xyz.z,
+        b: m[2][0] * xyz.x + m[2][1] * xyz.y + m[2][2] * xyz.z,
+    }
+}
+
+fn piecewise_gaussian(x: f32, mu: f32, sigma_lo: f32, sigma_hi: f32) -> f32 {
+    let sigma = match x < mu {
+        true => sigma_lo,
+        false => sigma_hi,
+    };
+    let t = (x - mu) / sigma;
+    (-0.5 * t * t).exp()
+}
@@ -0,0 +1,90 @@
+use crate::graphics::pixel::Pixel;
+
+const BIN_LAMBDA_MIN: f32 = 380.0;
+const BIN_LAMBDA_MAX: f32 = 720.0;
+const N_BINS: usize = 10;
+
+// Basis spectra from Smits, "An RGB-to-Spectrum Conversion for Reflectances" (1999).
+const WHITE: [f32; N_BINS] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const CYAN: [f32; N_BINS] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const MAGENTA: [f32; N_BINS] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const YELLOW: [f32; N_BINS] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const RED: [f32; N_BINS] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const GREEN: [f32; N_BINS] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const BLUE: [f32; N_BINS] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+// CIE standard illuminant D65 from 360nm to 830nm in 10nm steps.
+const D65_LAMBDA_MIN: f32 = 360.0;
+const D65_LAMBDA_MAX: f32 = 830.0;
+const D65: [f32; 48] = [
+    46.6383, 52.0891, 49.9755, 54.6482, 82.7549, 91.486, 93.4318, 86.6823, 104.865, 117.008,
+    117.812, 114.861, 115.923, 108.811, 109.354, 107.802, 104.79, 107.689, 104.405, 104.046, 100.0,
+    96.3342, 95.788, 88.6856, 90.0062, 89.5991, 87.6987, 83.2886, 83.6992, 80.0268, 80.2146,
+    82.2778, 78.2842, 69.7213, 71.6091, 74.349, 61.604, 69.8856, 75.087, 63.5927, 46.4182, 66.8054,
+    63.3828, 64.304, 59.4519, 51.959, 57.4406, 60.3125,
+];
+// Luminance of the D65 table against the fitted y-bar curve, so that the
+// normalized illuminant has Y = 1.
+const D65_Y: f32 = 98.850_99;
+
+// Emitted radiance: the reflectance uplift of the color lit by D65, so that
+// white lights are D65 white rather than equal-energy white.
+pub fn smits_illuminant(rgb: &Pixel, lambda: f32) -> f32 {
+    smits_reflectance(rgb, lambda) * d65(lambda) / D65_Y
+}
+
+pub fn smits_reflectance(rgb: &Pixel, lambda: f32) -> f32 {
+    let (r, g, b) = (rgb.r, rgb.g, rgb.b);
+    let basis = |spectrum: &[f32; N_BINS]| evaluate_bins(spectrum, lambda);
+
+    if r <= g && r <= b {
+        let white = r * basis(&WHITE);
+        match g <= b {
+            true => white + (g - r) * basis(&CYAN) + (b - g) * basis(&BLUE),
+            false => white + (b - r) * basis(&CYAN) + (g - b) * basis(&GREEN),
+        }
+    } else if g <= r && g <= b {
+        let white = g * basis(&WHITE);
+        match r <= b {
+            true => white + (r - g) * basis(&MAGENTA) + (b - r) * basis(&BLUE),
+            false => white + (b - g) * basis(&MAGENTA) + (r - b) * basis(&RED),
+        }
+    } else {
+        let white = b * basis(&WHITE);
+        match r <= g {
+            true => white + (r - b) * basis(&YELLOW) + (g - r) * basis(&GREEN),
+            false => white + (g - b) * basis(&YELLOW) + (r - g) * basis(&RED),
+        }
+    }
+}
+
+fn d65(lambda: f32) -> f32 {
+    let last = D65.len() - 1;
+    let bin_width = (D65_LAMBDA_MAX - D65_LAMBDA_MIN) / last as f32;
+    let x = ((lambda - D65_LAMBDA_MIN) / bin_width).clamp(0.0, last as f32);
+    let i = (x as usize).min(last - 1);
+    let t = x - i as f32;
+    (1.0 - t) * D65[i] + t * D65[i + 1]
+}
+
+fn evaluate_bins(spectrum: &[f32; N_BINS], lambda: f32) -> f32 {
+    let bin_width = (BIN_LAMBDA_MAX - BIN_LAMBDA_MIN) / (N_BINS - 1) as f32;
+    let x = ((lambda - BIN_LAMBDA_MIN) / bin_width).clamp(0.0, (N_BINS - 1) as f32);
+    let i = (x as usize).min(N_BINS - 2);
+    let t = x - i as f32;
+    (1.0 - t) * spectrum[i] + t * spectrum[i + 1]
+}
@@ -0,0 +1,147 @@
+use crate::graphics::{color_space::ColorSpace, pixel::Pixel};
+use crate::math::vec3::Vec3;
+use crate::spectral::{
+    color_matching, rgb_uplift,
+    sampled_wavelengths::{SampledWavelengths, N_SPECTRUM_SAMPLES},
+};
+
+use std::ops;
+
+#[derive(Default, Debug, Clone)]
+pub struct SampledSpectrum {
+    values: [f32; N_SPECTRUM_SAMPLES],
+}
+
+impl SampledSpectrum {
+    pub fn new(values: [f32; N_SPECTRUM_SAMPLES]) -> Self {
+        Self { values }
+    }
+
+    pub fn constant(val: f32) -> Self {
+        Self {
+            values: [val; N_SPECTRUM_SAMPLES],
+        }
+    }
+
+    // The uplift bases are fitted to sRGB primaries, so colors are brought
+    // into linear sRGB from `color_space` first.
+    pub fn from_rgb(
+        rgb: &Pixel,
+        color_space: ColorSpace,
+        wavelengths: &SampledWavelengths,
+    ) -> Self {
+        let rgb = color_space.convert(rgb, ColorSpace::LinearSrgb);
+        Self {
+            values: wavelengths
+                .lambda()
+                .map(|lambda| rgb_uplift::smits_reflectance(&rgb, lambda)),
+        }
+    }
+
+    pub fn from_rgb_illuminant(
+        rgb: &Pixel,
+        color_space: ColorSpace,
+        wavelengths: &SampledWavelengths,
+    ) -> Self {
+        let rgb = color_space.convert(rgb, ColorSpace::LinearSrgb);
+        Self {
+            values: wavelengths
+                .lambda()
+                .map(|lambda| rgb_uplift::smits_illuminant(&rgb, lambda)),
+        }
+    }
+
+    pub fn values(&self) -> &[f32; N_SPECTRUM_SAMPLES] {
+        &self.values
+    }
+
+    pub fn max_value(&self) -> f32 {
+        self.values.iter().copied().fold(f32::MIN, f32::max)
+    }
+
+    pub fn to_xyz(&self, wavelengths: &SampledWavelengths) -> Vec3 {
+        let xyz = self
+            .values
+            .iter()
+            .zip(wavelengths.lambda().iter().zip(wavelengths.pdf().iter()))
+            .filter(|(_, (_, &pdf))| pdf > 0.0)
+            .fold(Vec3::default(), |acc, (&val, (&lambda, &pdf))| {
+                acc + val / pdf * color_matching::cie_xyz(lambda)
+            });
+
+        xyz / (N_SPECTRUM_SAMPLES as f32 * color_matching::CIE_Y_INTEGRAL)
+    }
+
+    pub fn to_pixel(&self, wavelengths: &SampledWavelengths, color_space: ColorSpace) -> Pixel {
+        color_space.from_xyz(&self.to_xyz(wavelengths))
+    }
+}
+
+impl ops::Add for SampledSpectrum {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            values: std::array::from_fn(|i| self.values[i] + other.values[i]),
+        }
+    }
+}
+
+impl ops::Mul for SampledSpectrum {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            values: std::array::from_fn(|i| self.values[i] * other.values[i]),
+        }
+    }
+}
+
+impl ops::Mul<SampledSpectrum> for f32 {
+    type Output = SampledSpectrum;
+
+    fn mul(self, other: SampledSpectrum) -> Self::Output {
+        SampledSpectrum {
+            values: other.values.map(|val| self * val),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn average_pixel(
+        color_space: ColorSpace,
+        spectrum: impl Fn(&SampledWavelengths) -> SampledSpectrum,
+    ) -> Pixel {
+        let n = 4096;
+        let sum = (0..n).fold(Pixel::default(), |acc, i| {
+            let wavelengths = SampledWavelengths::sample_uniform((i as f32 + 0.5) / n as f32);
+            acc + spectrum(&wavelengths).to_pixel(&wavelengths, color_space)
+        });
+        sum * Pixel {
+            r: 1.0 / n as f32,
+            g: 1.0 / n as f32,
+            b: 1.0 / n as f32,
+        }
+    }
+
+    #[test]
+    fn white_light_on_white_reflector_is_white() {
+        let white = Pixel {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        };
+        for color_space in [ColorSpace::LinearSrgb, ColorSpace::AcesCg] {
+            let pixel = average_pixel(color_space, |wavelengths| {
+                SampledSpectrum::from_rgb(&white, color_space, wavelengths)
+                    * SampledSpectrum::from_rgb_illuminant(&white, color_space, wavelengths)
+            });
+            for channel in [pixel.r, pixel.g, pixel.b] {
+                assert!((channel - 1.0).abs() < 0.02, "{:?}", pixel);
+            }
+        }
+    }
+}
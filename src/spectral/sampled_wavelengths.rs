@@ -0,0 +1,64 @@
+use crate::sampling::sampler::Sampler;
+
+pub const LAMBDA_MIN: f32 = 360.0;
+pub const LAMBDA_MAX: f32 = 830.0;
+pub const N_SPECTRUM_SAMPLES: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct SampledWavelengths {
+    lambda: [f32; N_SPECTRUM_SAMPLES],
+    pdf: [f32; N_SPECTRUM_SAMPLES],
+}
+
+impl SampledWavelengths {
+    pub fn random(sampler: &mut dyn Sampler) -> Self {
+        SampledWavelengths::sample_uniform(sampler.get_1d())
+    }
+
+    // Hero wavelength sampling: the hero is drawn uniformly and the remaining
+    // wavelengths are placed at equal offsets, wrapping around the visible range.
+    pub fn sample_uniform(u: f32) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let delta = range / N_SPECTRUM_SAMPLES as f32;
+        let hero = LAMBDA_MIN + u * range;
+
+        let lambda = std::array::from_fn(|i| {
+            let lambda = hero + i as f32 * delta;
+            match lambda > LAMBDA_MAX {
+                true => lambda - range,
+                false => lambda,
+            }
+        });
+
+        Self {
+            lambda,
+            pdf: [1.0 / range; N_SPECTRUM_SAMPLES],
+        }
+    }
+
+    pub fn hero(&self) -> f32 {
+        self.lambda[0]
+    }
+
+    pub fn lambda(&self) -> &[f32; N_SPECTRUM_SAMPLES] {
+        &self.lambda
+    }
+
+    pub fn pdf(&self) -> &[f32; N_SPECTRUM_SAMPLES] {
+        &self.pdf
+    }
+
+    pub fn secondary_terminated(&self) -> bool {
+        self.pdf[1..].iter().all(|&pdf| pdf == 0.0)
+    }
+
+    // Wavelength-dependent scattering can only follow the hero wavelength, so
+    // the secondary wavelengths are dropped and the hero carries their weight.
+    pub fn terminate_secondary(&mut self) {
+        if self.secondary_terminated() {
+            return;
+        }
+        self.pdf[1..].iter_mut().for_each(|pdf| *pdf = 0.0);
+        self.pdf[0] /= N_SPECTRUM_SAMPLES as f32;
+    }
+}